@@ -0,0 +1,793 @@
+use crate::async_ssh_client::AsyncSSHClient;
+use crate::capabilities::{Capabilities, CapabilityPolicy, WithDefaultsMode, YangLibraryVersion};
+use crate::errors::NetconfClientError;
+use crate::errors::NetconfClientError::SSHClientError;
+use crate::framing::{self, MessageDecoder};
+use crate::messages;
+use crate::models::netconf_monitoring::{
+    DatastoreState, NetconfState, SchemaState, SessionState, Statistics,
+};
+use crate::models::subscribed_notifications::{SubscriptionId, SubscriptionStateChange};
+use crate::models::yang_library::{ModulesState, YangLibraryContent};
+use crate::models::{replies::*, requests::*};
+use crate::notification::Notification;
+use crate::observer::{Direction, MessageObserver, Redactor, Tracer};
+use crate::schema_cache::{self, SchemaCache, SchemaId};
+use crate::session::{ClientCapabilities, Framing, Session, YangLibraryCache};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Asynchronous counterpart of [`NetconfClient`](crate::netconf_client::NetconfClient).
+///
+/// Every operation is cancellation-safe: if a future is dropped before it
+/// completes, unsent bytes of its request are flushed and its reply is
+/// discarded by the next operation on the same client.
+pub struct AsyncNetconfClient {
+    ssh_client: AsyncSSHClient,
+    id: u32,
+    capabilities: ClientCapabilities,
+    session: Option<Session>,
+    capability_policy: CapabilityPolicy,
+    yang_library_cache: YangLibraryCache,
+    decoder: MessageDecoder,
+    outgoing: Vec<u8>,
+    tracer: Option<Tracer>,
+    notifications: VecDeque<Notification>,
+    subscription_active: bool,
+    dynamic_subscriptions: BTreeSet<SubscriptionId>,
+}
+
+impl AsyncNetconfClient {
+    pub fn new(host: &str, port: u16, user: &str, password: &str) -> AsyncNetconfClient {
+        AsyncNetconfClient {
+            ssh_client: AsyncSSHClient::create(host, port, user, password),
+            id: 0,
+            capabilities: ClientCapabilities::default(),
+            session: None,
+            capability_policy: CapabilityPolicy::default(),
+            yang_library_cache: YangLibraryCache::default(),
+            decoder: MessageDecoder::default(),
+            outgoing: Vec::new(),
+            tracer: None,
+            notifications: VecDeque::new(),
+            subscription_active: false,
+            dynamic_subscriptions: BTreeSet::new(),
+        }
+    }
+
+    pub fn get_session_id(&self) -> Option<u32> {
+        self.session.as_ref().map(|session| session.session_id)
+    }
+
+    pub fn get_session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    pub fn server_capabilities(&self) -> Option<&Capabilities> {
+        self.session
+            .as_ref()
+            .map(|session| &session.server_capabilities)
+    }
+
+    pub fn set_capability_policy(&mut self, policy: CapabilityPolicy) {
+        self.capability_policy = policy;
+    }
+
+    pub fn set_client_capabilities(&mut self, capabilities: ClientCapabilities) {
+        self.capabilities = capabilities;
+    }
+
+    pub fn set_observer(&mut self, observer: Arc<dyn MessageObserver>, redactor: Redactor) {
+        self.tracer = Some(Tracer::new(observer, redactor));
+    }
+
+    pub async fn connect(&mut self) -> Result<HelloServer, NetconfClientError> {
+        self.ssh_client.connect().await?;
+        // The hello exchange is always end-of-message framed, whatever the
+        // previous session negotiated.
+        self.session = None;
+        self.outgoing.clear();
+        self.decoder = MessageDecoder::default();
+        self.yang_library_cache = YangLibraryCache::default();
+        self.notifications.clear();
+        self.subscription_active = false;
+        self.dynamic_subscriptions.clear();
+        let reply = messages::parse_hello(&self.get_reply().await?)?;
+        let capabilities = self.capabilities.capabilities();
+        self.send(&messages::hello(&capabilities)).await?;
+        let session = Session::negotiate(capabilities, &reply);
+        self.decoder.set_framing(session.framing);
+        self.session = Some(session);
+        Ok(reply)
+    }
+
+    async fn get_reply(&mut self) -> Result<String, NetconfClientError> {
+        let message = self.read_message().await?;
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(Direction::Inbound, self.get_session_id(), &message);
+        }
+        Ok(message)
+    }
+
+    async fn read_message(&mut self) -> Result<String, NetconfClientError> {
+        loop {
+            if let Some(message) = self.decoder.next_message() {
+                return Ok(message);
+            }
+            let mut buffer = [0u8; 4096];
+            let bytes_read = self.ssh_client.read(&mut buffer[..]).await?;
+            self.decoder.push(&buffer[..bytes_read]);
+            if let Some(message) = self.decoder.next_message() {
+                return Ok(message);
+            }
+            if bytes_read == 0 || self.ssh_client.eof()? {
+                return Err(SSHClientError {
+                    err: "Buffer is empty, SSH channel read terminated".to_string(),
+                });
+            }
+        }
+    }
+
+    async fn send(&mut self, data: &str) -> Result<(), NetconfClientError> {
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(Direction::Outbound, self.get_session_id(), data);
+        }
+        self.outgoing
+            .extend_from_slice(framing::encode(data, self.framing()).as_bytes());
+        self.flush().await
+    }
+
+    async fn flush(&mut self) -> Result<(), NetconfClientError> {
+        while !self.outgoing.is_empty() {
+            let bytes_written = self.ssh_client.write(&self.outgoing).await?;
+            self.outgoing.drain(..bytes_written);
+        }
+        Ok(())
+    }
+
+    fn check_capabilities<F>(&self, check: F) -> Result<(), NetconfClientError>
+    where
+        F: FnOnce(&Capabilities) -> Result<(), NetconfClientError>,
+    {
+        match (&self.session, self.capability_policy) {
+            (Some(session), CapabilityPolicy::Strict) => check(&session.server_capabilities),
+            _ => Ok(()),
+        }
+    }
+
+    fn declare_module_prefixes(&self, mut filter: Option<Filter>) -> Option<Filter> {
+        if let (Some(filter), Some(capabilities)) = (filter.as_mut(), self.server_capabilities()) {
+            capabilities.declare_module_prefixes(filter);
+        }
+        filter
+    }
+
+    fn framing(&self) -> Framing {
+        self.session
+            .as_ref()
+            .map_or(Framing::EndOfMessage, |session| session.framing)
+    }
+
+    async fn request(&mut self, data: &str) -> Result<String, NetconfClientError> {
+        if self.subscription_active {
+            let operation = messages::rpc_operation(data).unwrap_or_default();
+            self.check_capabilities(|capabilities| capabilities.check_interleave(&operation))?;
+        }
+        self.send(data).await?;
+        loop {
+            let reply = self.get_reply().await?;
+            if messages::is_notification(&reply) {
+                self.receive_notification(&reply);
+                continue;
+            }
+            match messages::reply_message_id(&reply) {
+                Some(message_id) if message_id < self.id => continue,
+                _ => return Ok(reply),
+            }
+        }
+    }
+
+    fn receive_notification(&mut self, message: &str) {
+        let notification = Notification::parse(message);
+        if notification.is_notification_complete() {
+            self.subscription_active = false;
+        }
+        if let Ok(Some(change)) = SubscriptionStateChange::from_notification(&notification) {
+            if change.is_final() {
+                self.dynamic_subscriptions.remove(&change.id());
+            }
+        }
+        self.notifications.push_back(notification);
+    }
+
+    pub fn subscription_active(&self) -> bool {
+        self.subscription_active
+    }
+
+    /// Dynamic subscriptions established on this session which were not
+    /// deleted or terminated yet.
+    pub fn dynamic_subscriptions(&self) -> &BTreeSet<SubscriptionId> {
+        &self.dynamic_subscriptions
+    }
+
+    pub async fn create_subscription(
+        &mut self,
+        stream: Option<&str>,
+        filter: Option<NotificationFilter>,
+        start_time: Option<&str>,
+        stop_time: Option<&str>,
+    ) -> Result<CreateSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_create_subscription(&filter))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::create_subscription(
+                self.id, stream, filter, start_time, stop_time,
+            ))
+            .await?;
+        let reply: CreateSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        self.subscription_active = true;
+        Ok(reply)
+    }
+
+    pub async fn establish_subscription(
+        &mut self,
+        subscription: StreamSubscription,
+    ) -> Result<EstablishSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities
+                .check_subscribed_notifications(&subscription.filter, "establish-subscription")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::establish_subscription(self.id, subscription))
+            .await?;
+        let reply: EstablishSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        if let Some(id) = reply.id {
+            self.dynamic_subscriptions.insert(id);
+        }
+        Ok(reply)
+    }
+
+    /// Establishes a YANG-Push subscription. Updates are received as
+    /// notifications which can be decoded with
+    /// [`YangPushUpdate`](crate::models::yang_push::YangPushUpdate).
+    pub async fn establish_datastore_subscription(
+        &mut self,
+        subscription: DatastoreSubscription,
+    ) -> Result<EstablishSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_yang_push(&subscription))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::establish_datastore_subscription(
+                self.id,
+                subscription,
+            ))
+            .await?;
+        let reply: EstablishSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        if let Some(id) = reply.id {
+            self.dynamic_subscriptions.insert(id);
+        }
+        Ok(reply)
+    }
+
+    pub async fn modify_subscription(
+        &mut self,
+        id: SubscriptionId,
+        filter: Option<NotificationFilter>,
+        stop_time: Option<&str>,
+    ) -> Result<ModifySubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&filter, "modify-subscription")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::modify_subscription(
+                self.id, id, filter, stop_time,
+            ))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn delete_subscription(
+        &mut self,
+        id: SubscriptionId,
+    ) -> Result<DeleteSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&None, "delete-subscription")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::delete_subscription(self.id, id))
+            .await?;
+        let reply: DeleteSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        self.dynamic_subscriptions.remove(&id);
+        Ok(reply)
+    }
+
+    pub async fn kill_subscription(
+        &mut self,
+        id: SubscriptionId,
+    ) -> Result<KillSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&None, "kill-subscription")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::kill_subscription(self.id, id))
+            .await?;
+        let reply: KillSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        self.dynamic_subscriptions.remove(&id);
+        Ok(reply)
+    }
+
+    /// Returns the next notification, waiting for one while a subscription
+    /// is active. Returns `None` once the subscription has ended and all
+    /// received notifications were consumed.
+    pub async fn next_notification(&mut self) -> Result<Option<Notification>, NetconfClientError> {
+        loop {
+            if let Some(notification) = self.notifications.pop_front() {
+                return Ok(Some(notification));
+            }
+            if !self.subscription_active && self.dynamic_subscriptions.is_empty() {
+                return Ok(None);
+            }
+            self.flush().await?;
+            let message = self.get_reply().await?;
+            if messages::is_notification(&message) {
+                self.receive_notification(&message);
+            }
+        }
+    }
+
+    pub async fn kill_session(
+        &mut self,
+        session_id: u32,
+    ) -> Result<KillSessionRsp, NetconfClientError> {
+        self.id += 1;
+        let reply = self
+            .request(&messages::kill_session(self.id, session_id))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn close_session(&mut self) -> Result<CloseSessionRsp, NetconfClientError> {
+        self.id += 1;
+        let reply = self.request(&messages::close_session(self.id)).await?;
+        let reply: CloseSessionRsp = messages::parse_reply(&reply, self.id)?;
+        self.session = None;
+        self.ssh_client.disconnect().await?;
+        Ok(reply)
+    }
+
+    pub async fn get_config(
+        &mut self,
+        source: DatastoreType,
+        filter: Option<Filter>,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<GetConfigRsp, NetconfClientError> {
+        let filter = self.declare_module_prefixes(filter);
+        self.check_capabilities(|capabilities| {
+            capabilities.check_datastore(&source, "get-config")?;
+            capabilities.check_filter(&filter, "get-config")?;
+            capabilities.check_with_defaults(with_defaults, "get-config")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::get_config(
+                self.id,
+                source,
+                filter,
+                with_defaults,
+            ))
+            .await?;
+        messages::parse_get_config_reply(&reply, self.id, with_defaults)
+    }
+
+    pub async fn get(
+        &mut self,
+        filter: Option<Filter>,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<GetRsp, NetconfClientError> {
+        let filter = self.declare_module_prefixes(filter);
+        self.check_capabilities(|capabilities| {
+            capabilities.check_filter(&filter, "get")?;
+            capabilities.check_with_defaults(with_defaults, "get")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::get(self.id, filter, with_defaults))
+            .await?;
+        messages::parse_get_reply(&reply, self.id, with_defaults)
+    }
+
+    /// RFC 8526 `<get-data>`, which can also read the `operational` and
+    /// `intended` datastores.
+    pub async fn nmda_get_data(
+        &mut self,
+        datastore: Datastore,
+        mut options: GetDataOptions,
+    ) -> Result<GetDataRsp, NetconfClientError> {
+        options.filter = self.declare_module_prefixes(options.filter);
+        self.check_capabilities(|capabilities| capabilities.check_get_data(&options))?;
+        let with_defaults = options.with_defaults;
+        self.id += 1;
+        let reply = self
+            .request(&messages::nmda_get_data(self.id, datastore, options))
+            .await?;
+        messages::parse_get_data_reply(&reply, self.id, with_defaults)
+    }
+
+    pub async fn edit_data(
+        &mut self,
+        datastore: Datastore,
+        data: String,
+        default_operation: Option<DefaultOperationType>,
+    ) -> Result<EditDataRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_edit_data())?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::nmda_edit_data(
+                self.id,
+                datastore,
+                data,
+                default_operation,
+            ))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn edit_config(
+        &mut self,
+        source: DatastoreType,
+        data: String,
+        default_operation: Option<DefaultOperationType>,
+        test_option: Option<TestOptionType>,
+        error_option: Option<ErrorOptionType>,
+    ) -> Result<EditConfigRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_edit_config(&source, &test_option, &error_option)
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::edit_config(
+                self.id,
+                source,
+                data,
+                default_operation,
+                test_option,
+                error_option,
+            ))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    /// `<edit-config>` with the configuration loaded by the server from `url`.
+    pub async fn edit_config_url(
+        &mut self,
+        source: DatastoreType,
+        url: &str,
+        default_operation: Option<DefaultOperationType>,
+        test_option: Option<TestOptionType>,
+        error_option: Option<ErrorOptionType>,
+    ) -> Result<EditConfigRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_edit_config(&source, &test_option, &error_option)?;
+            capabilities.check_url(url, "edit-config")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::edit_config_url(
+                self.id,
+                source,
+                url,
+                default_operation,
+                test_option,
+                error_option,
+            ))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn lock(&mut self, target: DatastoreType) -> Result<LockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_datastore(&target, "lock"))?;
+        self.id += 1;
+        let reply = self.request(&messages::lock(self.id, target)).await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn unlock(&mut self, target: DatastoreType) -> Result<UnlockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_datastore(&target, "unlock"))?;
+        self.id += 1;
+        let reply = self.request(&messages::unlock(self.id, target)).await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn partial_lock(
+        &mut self,
+        select: Vec<String>,
+        namespaces: &BTreeMap<String, String>,
+    ) -> Result<PartialLockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_partial_lock("partial-lock"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::partial_lock(self.id, select, namespaces))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn partial_unlock(
+        &mut self,
+        lock_id: u32,
+    ) -> Result<PartialUnlockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_partial_lock("partial-unlock"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::partial_unlock(self.id, lock_id))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn delete_config(
+        &mut self,
+        target: impl Into<ConfigTargetType>,
+    ) -> Result<DeleteConfigRsp, NetconfClientError> {
+        let target = target.into();
+        self.check_capabilities(|capabilities| capabilities.check_delete_config(&target))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::delete_config(self.id, target))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn discard_changes(&mut self) -> Result<DiscardChangesRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_candidate("discard-changes"))?;
+        self.id += 1;
+        let reply = self.request(&messages::discard_changes(self.id)).await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn commit(&mut self) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_candidate("commit"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::commit(self.id, Commit::default()))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn confirmed_commit(
+        &mut self,
+        confirm_timeout: Option<u32>,
+        persist: Option<String>,
+    ) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_confirmed_commit("commit"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::confirmed_commit(
+                self.id,
+                confirm_timeout,
+                persist,
+            ))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn confirm_commit(
+        &mut self,
+        persist_id: Option<String>,
+    ) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_confirmed_commit("commit"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::confirm_commit(self.id, persist_id))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn cancel_commit(
+        &mut self,
+        persist_id: Option<String>,
+    ) -> Result<CancelCommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_confirmed_commit("cancel-commit")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::cancel_commit(self.id, persist_id))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn validate(
+        &mut self,
+        source: ValidateSourceType,
+    ) -> Result<ValidateRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_validate(&source))?;
+        self.id += 1;
+        let reply = self.request(&messages::validate(self.id, source)).await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn copy_config(
+        &mut self,
+        target: impl Into<ConfigTargetType>,
+        source: CopyConfigSourceType,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<CopyConfigRsp, NetconfClientError> {
+        let target = target.into();
+        self.check_capabilities(|capabilities| {
+            capabilities.check_copy_config(&target, &source)?;
+            capabilities.check_with_defaults(with_defaults, "copy-config")
+        })?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::copy_config(
+                self.id,
+                target,
+                source,
+                with_defaults,
+            ))
+            .await?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub async fn get_netconf_state(&mut self) -> Result<NetconfState, NetconfClientError> {
+        self.netconf_state(None).await
+    }
+
+    pub async fn get_sessions(&mut self) -> Result<Vec<SessionState>, NetconfClientError> {
+        let state = self.netconf_state(Some("sessions")).await?;
+        Ok(state.sessions.unwrap_or_default().sessions)
+    }
+
+    pub async fn get_datastores(&mut self) -> Result<Vec<DatastoreState>, NetconfClientError> {
+        let state = self.netconf_state(Some("datastores")).await?;
+        Ok(state.datastores.unwrap_or_default().datastores)
+    }
+
+    pub async fn get_statistics(&mut self) -> Result<Statistics, NetconfClientError> {
+        let state = self.netconf_state(Some("statistics")).await?;
+        Ok(state.statistics.unwrap_or_default())
+    }
+
+    pub async fn get_schema_list(&mut self) -> Result<Vec<SchemaState>, NetconfClientError> {
+        let state = self.netconf_state(Some("schemas")).await?;
+        Ok(state.schemas.unwrap_or_default().schemas)
+    }
+
+    async fn netconf_state(
+        &mut self,
+        container: Option<&str>,
+    ) -> Result<NetconfState, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_monitoring("get"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::get_netconf_state(self.id, container))
+            .await?;
+        messages::parse_data_reply(&reply, self.id)
+    }
+
+    pub async fn get_schema(
+        &mut self,
+        identifier: &str,
+        version: Option<&str>,
+        format: Option<SchemaFormat>,
+    ) -> Result<GetSchemaRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_monitoring("get-schema"))?;
+        self.id += 1;
+        let reply = self
+            .request(&messages::get_schema(self.id, identifier, version, format))
+            .await?;
+        messages::parse_get_schema_reply(&reply, self.id)
+    }
+
+    /// Downloads schemas of all modules listed in the hello and in the YANG
+    /// library which are not in the cache yet. Schemas whose identifier or
+    /// revision is not a safe file name are skipped. Returns paths of new
+    /// files.
+    pub async fn download_schemas(
+        &mut self,
+        cache: &SchemaCache,
+        format: SchemaFormat,
+    ) -> Result<Vec<PathBuf>, NetconfClientError> {
+        let mut downloaded = Vec::new();
+        for schema in self.schema_ids().await? {
+            if cache.contains(&schema, format).unwrap_or(true) {
+                continue;
+            }
+            let rsp = self
+                .get_schema(&schema.identifier, schema.revision.as_deref(), Some(format))
+                .await?;
+            downloaded.push(cache.store(&schema, format, &rsp.schema)?);
+        }
+        Ok(downloaded)
+    }
+
+    async fn schema_ids(&mut self) -> Result<Vec<SchemaId>, NetconfClientError> {
+        let (mut schemas, yang_library) = match self.server_capabilities() {
+            Some(capabilities) => (
+                schema_cache::hello_schemas(capabilities),
+                capabilities
+                    .yang_library()
+                    .map(|yang_library| yang_library.version),
+            ),
+            None => (Vec::new(), None),
+        };
+        match yang_library {
+            Some(YangLibraryVersion::V1_1) => schemas.extend(schema_cache::yang_library_schemas(
+                &self.get_yang_library().await?,
+            )),
+            Some(YangLibraryVersion::V1_0) => schemas.extend(schema_cache::modules_state_schemas(
+                &self.get_modules_state().await?,
+            )),
+            None => (),
+        }
+        schemas.sort();
+        schemas.dedup();
+        Ok(schemas)
+    }
+
+    pub async fn raw_rpc(&mut self, data: &str) -> Result<RawRpcRsp, NetconfClientError> {
+        self.id += 1;
+        let reply = self.request(&messages::raw_rpc(self.id, data)).await?;
+        messages::parse_raw_rpc_reply(&reply, self.id)
+    }
+
+    /// Invokes an RPC defined in the module with the given namespace. The
+    /// output leaves of the reply are deserialized into `Rsp`.
+    pub async fn rpc<Req: Serialize, Rsp: DeserializeOwned>(
+        &mut self,
+        namespace: &str,
+        input: &Req,
+    ) -> Result<Rsp, NetconfClientError> {
+        let data = messages::rpc_input(namespace, input)?;
+        self.id += 1;
+        let reply = self.request(&messages::raw_rpc(self.id, &data)).await?;
+        messages::parse_rpc_reply(&reply, self.id)
+    }
+
+    /// Invokes a YANG 1.1 action on the data node at `path`. The output
+    /// leaves of the reply are deserialized into `Rsp`.
+    pub async fn action<Req: Serialize, Rsp: DeserializeOwned>(
+        &mut self,
+        path: &ActionPath,
+        input: &Req,
+    ) -> Result<Rsp, NetconfClientError> {
+        let data = messages::action_input(path, input)?;
+        self.id += 1;
+        let reply = self.request(&messages::raw_rpc(self.id, &data)).await?;
+        messages::parse_rpc_reply(&reply, self.id)
+    }
+
+    pub async fn get_modules_state(&mut self) -> Result<ModulesState, NetconfClientError> {
+        let content_id = self.session.as_ref().and_then(Session::content_id);
+        if let Some(state) = self.yang_library_cache.modules_state(content_id) {
+            return Ok(state.clone());
+        }
+        self.id += 1;
+        let reply = self.request(&messages::get_modules_state(self.id)).await?;
+        let state: ModulesState = messages::parse_data_reply(&reply, self.id)?;
+        self.yang_library_cache.modules_state = Some(state.clone());
+        Ok(state)
+    }
+
+    pub async fn get_yang_library(&mut self) -> Result<YangLibraryContent, NetconfClientError> {
+        let content_id = self.session.as_ref().and_then(Session::content_id);
+        if let Some(library) = self.yang_library_cache.yang_library(content_id) {
+            return Ok(library.clone());
+        }
+        self.id += 1;
+        let reply = self.request(&messages::get_yang_library(self.id)).await?;
+        let library: YangLibraryContent = messages::parse_data_reply(&reply, self.id)?;
+        self.yang_library_cache.yang_library = Some(library.clone());
+        Ok(library)
+    }
+
+    pub fn invalidate_yang_library_cache(&mut self) {
+        self.yang_library_cache = YangLibraryCache::default();
+    }
+}
@@ -0,0 +1,178 @@
+use crate::errors::NetconfClientError;
+use ssh2::{BlockDirections, Channel, ErrorCode, Session};
+use std::io;
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use tokio::io::Interest;
+use tokio::net::TcpStream;
+
+const LIBSSH2_ERROR_EAGAIN: i32 = -37;
+
+pub struct AsyncSSHClient {
+    host: String,
+    port: u16,
+    user: String,
+    password: String,
+    connection: Option<Connection>,
+}
+
+struct Connection {
+    stream: TcpStream,
+    session: Session,
+    channel: Channel,
+}
+
+impl AsyncSSHClient {
+    pub fn create(host: &str, port: u16, user: &str, password: &str) -> AsyncSSHClient {
+        AsyncSSHClient {
+            host: host.to_owned(),
+            port,
+            user: user.to_owned(),
+            password: password.to_owned(),
+            connection: None,
+        }
+    }
+
+    pub async fn connect(&mut self) -> Result<(), NetconfClientError> {
+        let ip_addr = IpAddr::from_str(&self.host)?;
+        let socket_address: SocketAddr = SocketAddr::from((ip_addr, self.port));
+        let tcp = TcpStream::connect(socket_address).await?.into_std()?;
+        let mut session = Session::new()?;
+
+        session.set_tcp_stream(tcp.try_clone()?);
+        session.set_blocking(false);
+        let stream = TcpStream::from_std(tcp)?;
+
+        let mut handshake_session = session.clone();
+        retry(&stream, &session, || handshake_session.handshake()).await?;
+        retry(&stream, &session, || {
+            session.userauth_password(&self.user, &self.password)
+        })
+        .await?;
+
+        let mut channel = retry(&stream, &session, || session.channel_session()).await?;
+        retry(&stream, &session, || channel.subsystem("netconf")).await?;
+        self.connection = Some(Connection {
+            stream,
+            session,
+            channel,
+        });
+        Ok(())
+    }
+
+    fn connection(&mut self) -> io::Result<&mut Connection> {
+        self.connection
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "SSH channel not connected"))
+    }
+
+    pub fn eof(&mut self) -> io::Result<bool> {
+        Ok(self.connection()?.channel.eof())
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), NetconfClientError> {
+        let Connection {
+            stream,
+            session,
+            channel,
+        } = self.connection()?;
+        retry(stream, session, || channel.send_eof()).await?;
+        retry(stream, session, || channel.wait_eof()).await?;
+        retry(stream, session, || channel.close()).await?;
+        retry(stream, session, || channel.wait_close()).await?;
+        self.connection = None;
+        Ok(())
+    }
+
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Connection {
+            stream,
+            session,
+            channel,
+        } = self.connection()?;
+        loop {
+            match channel.read(buf) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    wait_for_socket(stream, session).await?
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Connection {
+            stream,
+            session,
+            channel,
+        } = self.connection()?;
+        loop {
+            match channel.write(buf) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    wait_for_socket(stream, session).await?
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+async fn retry<T, F>(
+    stream: &TcpStream,
+    session: &Session,
+    mut operation: F,
+) -> Result<T, NetconfClientError>
+where
+    F: FnMut() -> Result<T, ssh2::Error>,
+{
+    loop {
+        match operation() {
+            Err(err) if err.code() == ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => {
+                wait_for_socket(stream, session).await?
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+async fn wait_for_socket(stream: &TcpStream, session: &Session) -> io::Result<()> {
+    let interest = match session.block_directions() {
+        BlockDirections::Outbound => Interest::WRITABLE,
+        BlockDirections::Both => Interest::READABLE | Interest::WRITABLE,
+        BlockDirections::Inbound | BlockDirections::None => Interest::READABLE,
+    };
+    stream.ready(interest).await?;
+    // libssh2 does the actual socket IO, so readiness has to be cleared here
+    // for the next wait to block until the socket state changes again.
+    let _ = stream.try_io(interest, || Err::<(), _>(io::ErrorKind::WouldBlock.into()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn not_connected() {
+        let mut client = AsyncSSHClient::create("127.0.0.1", 830, "user", "password");
+        assert_eq!(
+            client.eof().unwrap_err().kind(),
+            io::ErrorKind::NotConnected
+        );
+        assert_eq!(
+            client.read(&mut [0u8; 16]).await.unwrap_err().kind(),
+            io::ErrorKind::NotConnected
+        );
+        assert_eq!(
+            client.write(b"<hello/>").await.unwrap_err().kind(),
+            io::ErrorKind::NotConnected
+        );
+        match client.disconnect().await {
+            Err(NetconfClientError::IOError(err)) => {
+                assert_eq!(err.kind(), io::ErrorKind::NotConnected)
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
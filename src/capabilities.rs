@@ -0,0 +1,986 @@
+use crate::consts;
+use crate::errors::NetconfClientError;
+use crate::errors::NetconfClientError::CapabilityNotSupported;
+use crate::models::requests::{
+    ConfigTargetType, CopyConfigSourceType, DatastoreSubscription, DatastoreType, ErrorOptionType,
+    Filter, FilterType, GetDataOptions, NotificationFilter, TestOptionType, UpdateTrigger,
+    ValidateSourceType,
+};
+use crate::models::subscribed_notifications::SUBSCRIBED_NOTIFICATIONS_NAMESPACE;
+use crate::models::yang_push::YANG_PUSH_NAMESPACE;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+const CAPABILITY_PREFIX: &str = "urn:ietf:params:netconf:capability:";
+
+/// Whether operations are checked against server capabilities before being
+/// sent. `Lenient` is meant for devices which under-advertise.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CapabilityPolicy {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Capability {
+    Base1_0,
+    Base1_1,
+    WritableRunning,
+    Candidate,
+    ConfirmedCommit1_0,
+    ConfirmedCommit1_1,
+    RollbackOnError,
+    Validate1_0,
+    Validate1_1,
+    Startup,
+    Url { schemes: Vec<String> },
+    XPath,
+    Notification,
+    Interleave,
+    PartialLock,
+    WithDefaults(WithDefaults),
+    YangLibrary(YangLibrary),
+    Module(Module),
+    Other(String),
+}
+
+#[derive(strum_macros::Display, strum_macros::EnumString, Debug, Clone, Copy, PartialEq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum WithDefaultsMode {
+    ReportAll,
+    ReportAllTagged,
+    Trim,
+    Explicit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithDefaults {
+    pub basic_mode: WithDefaultsMode,
+    pub also_supported: Vec<WithDefaultsMode>,
+}
+
+impl WithDefaults {
+    pub fn supports(&self, mode: WithDefaultsMode) -> bool {
+        self.basic_mode == mode || self.also_supported.contains(&mode)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YangLibraryVersion {
+    /// RFC 7950 capability, announced with `module-set-id`.
+    V1_0,
+    /// RFC 8526 capability, announced with `content-id`.
+    V1_1,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YangLibrary {
+    pub version: YangLibraryVersion,
+    pub revision: Option<String>,
+    /// `content-id` for yang-library:1.1, `module-set-id` for yang-library:1.0.
+    pub content_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub namespace: String,
+    pub name: String,
+    pub revision: Option<String>,
+    pub features: Vec<String>,
+    pub deviations: Vec<String>,
+}
+
+impl Capability {
+    pub fn parse(uri: &str) -> Capability {
+        let uri = uri.trim();
+        let (base, query) = match uri.find('?') {
+            Some(index) => (&uri[..index], &uri[index + 1..]),
+            None => (uri, ""),
+        };
+        let params: Vec<(&str, &str)> = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| match param.find('=') {
+                Some(index) => (&param[..index], &param[index + 1..]),
+                None => (param, ""),
+            })
+            .collect();
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+        let list = |name: &str| -> Vec<String> {
+            param(name)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .filter(|item| !item.is_empty())
+                        .map(|item| item.to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        match base {
+            "urn:ietf:params:netconf:base:1.0" => return Capability::Base1_0,
+            "urn:ietf:params:netconf:base:1.1" => return Capability::Base1_1,
+            _ => (),
+        }
+        if let Some(name) = base.strip_prefix(CAPABILITY_PREFIX) {
+            let capability = match name {
+                "writable-running:1.0" => Some(Capability::WritableRunning),
+                "candidate:1.0" => Some(Capability::Candidate),
+                "confirmed-commit:1.0" => Some(Capability::ConfirmedCommit1_0),
+                "confirmed-commit:1.1" => Some(Capability::ConfirmedCommit1_1),
+                "rollback-on-error:1.0" => Some(Capability::RollbackOnError),
+                "validate:1.0" => Some(Capability::Validate1_0),
+                "validate:1.1" => Some(Capability::Validate1_1),
+                "startup:1.0" => Some(Capability::Startup),
+                "url:1.0" => Some(Capability::Url {
+                    schemes: list("scheme"),
+                }),
+                "xpath:1.0" => Some(Capability::XPath),
+                "notification:1.0" => Some(Capability::Notification),
+                "interleave:1.0" => Some(Capability::Interleave),
+                "partial-lock:1.0" => Some(Capability::PartialLock),
+                "with-defaults:1.0" => param("basic-mode")
+                    .and_then(|mode| WithDefaultsMode::from_str(mode).ok())
+                    .map(|basic_mode| {
+                        Capability::WithDefaults(WithDefaults {
+                            basic_mode,
+                            also_supported: list("also-supported")
+                                .iter()
+                                .filter_map(|mode| WithDefaultsMode::from_str(mode).ok())
+                                .collect(),
+                        })
+                    }),
+                "yang-library:1.0" => Some(Capability::YangLibrary(YangLibrary {
+                    version: YangLibraryVersion::V1_0,
+                    revision: param("revision").map(|value| value.to_string()),
+                    content_id: param("module-set-id").map(|value| value.to_string()),
+                })),
+                "yang-library:1.1" => Some(Capability::YangLibrary(YangLibrary {
+                    version: YangLibraryVersion::V1_1,
+                    revision: param("revision").map(|value| value.to_string()),
+                    content_id: param("content-id").map(|value| value.to_string()),
+                })),
+                _ => None,
+            };
+            if let Some(capability) = capability {
+                return capability;
+            }
+        }
+        if let Some(name) = param("module") {
+            return Capability::Module(Module {
+                namespace: base.to_string(),
+                name: name.to_string(),
+                revision: param("revision").map(|value| value.to_string()),
+                features: list("features"),
+                deviations: list("deviations"),
+            });
+        }
+        Capability::Other(uri.to_string())
+    }
+}
+
+/// Capabilities announced in the server `<hello>`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Capabilities {
+    uris: Vec<String>,
+    capabilities: Vec<Capability>,
+}
+
+impl Capabilities {
+    pub fn parse<S: AsRef<str>>(uris: &[S]) -> Capabilities {
+        Capabilities {
+            uris: uris
+                .iter()
+                .map(|uri| uri.as_ref().trim().to_string())
+                .collect(),
+            capabilities: uris
+                .iter()
+                .map(|uri| Capability::parse(uri.as_ref()))
+                .collect(),
+        }
+    }
+
+    pub fn uris(&self) -> &[String] {
+        &self.uris
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Capability> {
+        self.capabilities.iter()
+    }
+
+    pub fn contains(&self, capability: &Capability) -> bool {
+        self.capabilities.contains(capability)
+    }
+
+    pub fn has_uri(&self, uri: &str) -> bool {
+        self.uris.iter().any(|item| item == uri)
+    }
+
+    pub fn base_1_1(&self) -> bool {
+        self.contains(&Capability::Base1_1)
+    }
+
+    pub fn writable_running(&self) -> bool {
+        self.contains(&Capability::WritableRunning)
+    }
+
+    pub fn candidate(&self) -> bool {
+        self.contains(&Capability::Candidate)
+    }
+
+    pub fn confirmed_commit(&self) -> bool {
+        self.contains(&Capability::ConfirmedCommit1_0) || self.confirmed_commit_1_1()
+    }
+
+    pub fn confirmed_commit_1_1(&self) -> bool {
+        self.contains(&Capability::ConfirmedCommit1_1)
+    }
+
+    pub fn rollback_on_error(&self) -> bool {
+        self.contains(&Capability::RollbackOnError)
+    }
+
+    pub fn validate(&self) -> bool {
+        self.contains(&Capability::Validate1_0) || self.validate_1_1()
+    }
+
+    pub fn validate_1_1(&self) -> bool {
+        self.contains(&Capability::Validate1_1)
+    }
+
+    pub fn startup(&self) -> bool {
+        self.contains(&Capability::Startup)
+    }
+
+    pub fn url_schemes(&self) -> Option<&[String]> {
+        self.iter().find_map(|capability| match capability {
+            Capability::Url { schemes } => Some(schemes.as_slice()),
+            _ => None,
+        })
+    }
+
+    pub fn xpath(&self) -> bool {
+        self.contains(&Capability::XPath)
+    }
+
+    pub fn notification(&self) -> bool {
+        self.contains(&Capability::Notification)
+    }
+
+    pub fn interleave(&self) -> bool {
+        self.contains(&Capability::Interleave)
+    }
+
+    pub fn partial_lock(&self) -> bool {
+        self.contains(&Capability::PartialLock)
+    }
+
+    pub fn with_defaults(&self) -> Option<&WithDefaults> {
+        self.iter().find_map(|capability| match capability {
+            Capability::WithDefaults(with_defaults) => Some(with_defaults),
+            _ => None,
+        })
+    }
+
+    pub fn yang_library(&self) -> Option<&YangLibrary> {
+        self.iter().find_map(|capability| match capability {
+            Capability::YangLibrary(yang_library) => Some(yang_library),
+            _ => None,
+        })
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = &Module> {
+        self.iter().filter_map(|capability| match capability {
+            Capability::Module(module) => Some(module),
+            _ => None,
+        })
+    }
+
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.modules().find(|module| module.name == name)
+    }
+
+    pub(crate) fn require(
+        &self,
+        supported: bool,
+        capability: &str,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        if supported {
+            Ok(())
+        } else {
+            Err(CapabilityNotSupported {
+                capability: capability.to_string(),
+                operation: operation.to_string(),
+            })
+        }
+    }
+
+    pub(crate) fn check_datastore(
+        &self,
+        datastore: &DatastoreType,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        match datastore {
+            DatastoreType::Running => Ok(()),
+            DatastoreType::Candidate => self.require(
+                self.candidate(),
+                "urn:ietf:params:netconf:capability:candidate:1.0",
+                operation,
+            ),
+            DatastoreType::Startup => self.require(
+                self.startup(),
+                "urn:ietf:params:netconf:capability:startup:1.0",
+                operation,
+            ),
+        }
+    }
+
+    pub(crate) fn check_candidate(&self, operation: &str) -> Result<(), NetconfClientError> {
+        self.check_datastore(&DatastoreType::Candidate, operation)
+    }
+
+    pub(crate) fn check_confirmed_commit(&self, operation: &str) -> Result<(), NetconfClientError> {
+        self.check_candidate(operation)?;
+        self.require(
+            self.confirmed_commit_1_1(),
+            "urn:ietf:params:netconf:capability:confirmed-commit:1.1",
+            operation,
+        )
+    }
+
+    pub(crate) fn check_partial_lock(&self, operation: &str) -> Result<(), NetconfClientError> {
+        self.require(
+            self.partial_lock(),
+            "urn:ietf:params:netconf:capability:partial-lock:1.0",
+            operation,
+        )
+    }
+
+    pub(crate) fn check_create_subscription(
+        &self,
+        filter: &Option<NotificationFilter>,
+    ) -> Result<(), NetconfClientError> {
+        self.require(
+            self.notification(),
+            "urn:ietf:params:netconf:capability:notification:1.0",
+            "create-subscription",
+        )?;
+        match filter {
+            Some(NotificationFilter::XPath { .. }) => self.require(
+                self.xpath(),
+                "urn:ietf:params:netconf:capability:xpath:1.0",
+                "create-subscription",
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Without `:interleave` the server processes only `<close-session>`
+    /// while a subscription is active.
+    pub(crate) fn check_interleave(&self, operation: &str) -> Result<(), NetconfClientError> {
+        match operation {
+            "close-session" => Ok(()),
+            _ => self.require(
+                self.interleave(),
+                "urn:ietf:params:netconf:capability:interleave:1.0",
+                operation,
+            ),
+        }
+    }
+
+    /// Modules are listed in `<hello>` only up to YANG library 1.0, with 1.1
+    /// they have to be looked up in the YANG library so the check is left to
+    /// the server.
+    pub(crate) fn check_module(
+        &self,
+        name: &str,
+        namespace: &str,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        let yang_library_1_1 = matches!(
+            self.yang_library(),
+            Some(YangLibrary {
+                version: YangLibraryVersion::V1_1,
+                ..
+            })
+        );
+        self.require(
+            yang_library_1_1 || self.module(name).is_some(),
+            namespace,
+            operation,
+        )
+    }
+
+    pub(crate) fn check_subscribed_notifications(
+        &self,
+        filter: &Option<NotificationFilter>,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        self.check_module(
+            "ietf-subscribed-notifications",
+            SUBSCRIBED_NOTIFICATIONS_NAMESPACE,
+            operation,
+        )?;
+        match filter {
+            Some(NotificationFilter::XPath { .. }) => self.require(
+                self.xpath(),
+                "urn:ietf:params:netconf:capability:xpath:1.0",
+                operation,
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_with_defaults(
+        &self,
+        mode: Option<WithDefaultsMode>,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        let mode = match mode {
+            Some(mode) => mode,
+            None => return Ok(()),
+        };
+        self.require(
+            self.with_defaults()
+                .is_some_and(|with_defaults| with_defaults.supports(mode)),
+            &format!(
+                "{}with-defaults:1.0?also-supported={}",
+                CAPABILITY_PREFIX, mode
+            ),
+            operation,
+        )
+    }
+
+    pub(crate) fn check_get_data(
+        &self,
+        options: &GetDataOptions,
+    ) -> Result<(), NetconfClientError> {
+        self.check_module("ietf-netconf-nmda", consts::NMDA_XMLNS, "get-data")?;
+        self.check_filter(&options.filter, "get-data")?;
+        if let (true, Some(module)) = (options.with_origin, self.module("ietf-netconf-nmda")) {
+            self.require(
+                module.features.iter().any(|feature| feature == "origin"),
+                &format!(
+                    "{}?module=ietf-netconf-nmda&features=origin",
+                    consts::NMDA_XMLNS
+                ),
+                "get-data",
+            )?;
+        }
+        self.check_with_defaults(options.with_defaults, "get-data")
+    }
+
+    pub(crate) fn check_edit_data(&self) -> Result<(), NetconfClientError> {
+        self.check_module("ietf-netconf-nmda", consts::NMDA_XMLNS, "edit-data")
+    }
+
+    pub(crate) fn check_monitoring(&self, operation: &str) -> Result<(), NetconfClientError> {
+        self.check_module(
+            "ietf-netconf-monitoring",
+            consts::MONITORING_XMLNS,
+            operation,
+        )
+    }
+
+    pub(crate) fn check_yang_push(
+        &self,
+        subscription: &DatastoreSubscription,
+    ) -> Result<(), NetconfClientError> {
+        self.check_subscribed_notifications(&subscription.filter, "establish-subscription")?;
+        self.check_module(
+            "ietf-yang-push",
+            YANG_PUSH_NAMESPACE,
+            "establish-subscription",
+        )?;
+        match (&subscription.trigger, self.module("ietf-yang-push")) {
+            (UpdateTrigger::OnChange { .. }, Some(module)) => self.require(
+                module.features.iter().any(|feature| feature == "on-change"),
+                &format!(
+                    "{}?module=ietf-yang-push&features=on-change",
+                    YANG_PUSH_NAMESPACE
+                ),
+                "establish-subscription",
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_writable(
+        &self,
+        target: &DatastoreType,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        match target {
+            DatastoreType::Running => self.require(
+                self.writable_running(),
+                "urn:ietf:params:netconf:capability:writable-running:1.0",
+                operation,
+            ),
+            _ => self.check_datastore(target, operation),
+        }
+    }
+
+    pub(crate) fn check_edit_config(
+        &self,
+        target: &DatastoreType,
+        test_option: &Option<TestOptionType>,
+        error_option: &Option<ErrorOptionType>,
+    ) -> Result<(), NetconfClientError> {
+        self.check_writable(target, "edit-config")?;
+        match test_option {
+            Some(TestOptionType::TestOnly) => self.require(
+                self.validate_1_1(),
+                "urn:ietf:params:netconf:capability:validate:1.1",
+                "edit-config",
+            )?,
+            Some(_) => self.require(
+                self.validate(),
+                "urn:ietf:params:netconf:capability:validate",
+                "edit-config",
+            )?,
+            None => (),
+        }
+        if let Some(ErrorOptionType::RollbackOnError) = error_option {
+            self.require(
+                self.rollback_on_error(),
+                "urn:ietf:params:netconf:capability:rollback-on-error:1.0",
+                "edit-config",
+            )?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_validate(
+        &self,
+        source: &ValidateSourceType,
+    ) -> Result<(), NetconfClientError> {
+        self.require(
+            self.validate(),
+            "urn:ietf:params:netconf:capability:validate",
+            "validate",
+        )?;
+        match source {
+            ValidateSourceType::Datastore { source } => self.check_datastore(source, "validate"),
+            ValidateSourceType::Config { .. } => Ok(()),
+        }
+    }
+
+    /// The scheme of the URL has to be listed in the `:url` capability.
+    pub(crate) fn check_url(&self, url: &str, operation: &str) -> Result<(), NetconfClientError> {
+        let scheme = url.find(':').map_or("", |index| &url[..index]);
+        self.require(
+            self.url_schemes().is_some_and(|schemes| {
+                !scheme.is_empty()
+                    && schemes
+                        .iter()
+                        .any(|supported| supported.eq_ignore_ascii_case(scheme))
+            }),
+            &format!("{}url:1.0?scheme={}", CAPABILITY_PREFIX, scheme),
+            operation,
+        )
+    }
+
+    pub(crate) fn check_copy_config(
+        &self,
+        target: &ConfigTargetType,
+        source: &CopyConfigSourceType,
+    ) -> Result<(), NetconfClientError> {
+        match target {
+            ConfigTargetType::Datastore { target } => self.check_writable(target, "copy-config")?,
+            ConfigTargetType::Url { url } => self.check_url(&url.value, "copy-config")?,
+        }
+        match source {
+            CopyConfigSourceType::Datastore { source } => {
+                self.check_datastore(source, "copy-config")
+            }
+            CopyConfigSourceType::Config { .. } => Ok(()),
+            CopyConfigSourceType::Url { url } => self.check_url(&url.value, "copy-config"),
+        }
+    }
+
+    pub(crate) fn check_delete_config(
+        &self,
+        target: &ConfigTargetType,
+    ) -> Result<(), NetconfClientError> {
+        match target {
+            ConfigTargetType::Datastore { target } => self.check_datastore(target, "delete-config"),
+            ConfigTargetType::Url { url } => self.check_url(&url.value, "delete-config"),
+        }
+    }
+
+    pub(crate) fn check_filter(
+        &self,
+        filter: &Option<Filter>,
+        operation: &str,
+    ) -> Result<(), NetconfClientError> {
+        match filter {
+            Some(Filter {
+                filter_type: FilterType::XPath,
+                ..
+            }) => self.require(
+                self.xpath(),
+                "urn:ietf:params:netconf:capability:xpath:1.0",
+                operation,
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Declares the XPath prefixes which are not declared by the caller but
+    /// match the name of a module announced in `<hello>`.
+    pub(crate) fn declare_module_prefixes(&self, filter: &mut Filter) {
+        let select = match (&filter.filter_type, &filter.select) {
+            (FilterType::XPath, Some(select)) => select,
+            _ => return,
+        };
+        for prefix in xpath_prefixes(select) {
+            if filter.namespaces.contains_key(prefix) {
+                continue;
+            }
+            if let Some(module) = self.module(prefix) {
+                filter
+                    .namespaces
+                    .insert(prefix.to_string(), module.namespace.clone());
+            }
+        }
+    }
+}
+
+/// Prefixes of the qualified names in an XPath expression, skipping string
+/// literals and axis names.
+fn xpath_prefixes(select: &str) -> BTreeSet<&str> {
+    let mut prefixes = BTreeSet::new();
+    let mut quote = None;
+    let mut start = None;
+    for (index, c) in select.char_indices() {
+        if let Some(delimiter) = quote {
+            if c == delimiter {
+                quote = None;
+            }
+            continue;
+        }
+        if c.is_alphanumeric() || ['-', '_', '.'].contains(&c) {
+            start.get_or_insert(index);
+            continue;
+        }
+        if let Some(begin) = start.take() {
+            let name = &select[begin..index];
+            let is_name = name.starts_with(|c: char| c.is_alphabetic() || c == '_');
+            if c == ':' && is_name && !select[index + 1..].starts_with(':') {
+                prefixes.insert(name);
+            }
+        }
+        if ['\'', '"'].contains(&c) {
+            quote = Some(c);
+        }
+    }
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::requests::Url;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn xpath_filter_prefixes() {
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:xml:ns:yang:ietf-interfaces?module=ietf-interfaces&revision=2018-02-20",
+            "ns:yang:test?module=test-model",
+        ]);
+        assert_eq!(
+            xpath_prefixes("/if:interfaces/if:interface[if:name='a:b'][child::t:x]/@nc:operation")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["if", "nc", "t"]
+        );
+
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("test-model".to_string(), "urn:example:test".to_string());
+        let mut filter = Filter::xpath(
+            "/ietf-interfaces:interfaces | /test-model:users | /unknown:x",
+            namespaces,
+        );
+        capabilities.declare_module_prefixes(&mut filter);
+        assert_eq!(filter.namespaces.len(), 2);
+        assert_eq!(
+            filter.namespaces["ietf-interfaces"],
+            "urn:ietf:params:xml:ns:yang:ietf-interfaces"
+        );
+        assert_eq!(filter.namespaces["test-model"], "urn:example:test");
+
+        match capabilities.check_filter(&Some(filter), "get") {
+            Err(CapabilityNotSupported { capability, .. }) => {
+                assert_eq!(capability, "urn:ietf:params:netconf:capability:xpath:1.0")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(capabilities
+            .check_filter(&Some(Filter::subtree("<users/>")), "get")
+            .is_ok());
+    }
+
+    #[test]
+    fn url_schemes() {
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:netconf:capability:startup:1.0",
+            "urn:ietf:params:netconf:capability:url:1.0?scheme=ftp,file",
+        ]);
+        assert!(capabilities
+            .check_copy_config(
+                &ConfigTargetType::Url {
+                    url: Url::new("FILE:///backup.xml"),
+                },
+                &CopyConfigSourceType::Datastore {
+                    source: DatastoreType::Startup,
+                },
+            )
+            .is_ok());
+        match capabilities.check_delete_config(&ConfigTargetType::Url {
+            url: Url::new("https://example.com/backup.xml"),
+        }) {
+            Err(CapabilityNotSupported {
+                capability,
+                operation,
+            }) => {
+                assert_eq!(
+                    capability,
+                    "urn:ietf:params:netconf:capability:url:1.0?scheme=https"
+                );
+                assert_eq!(operation, "delete-config");
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(capabilities.check_url("backup.xml", "edit-config").is_err());
+        assert!(Capabilities::parse(&["urn:ietf:params:netconf:base:1.1"])
+            .check_url("file:///backup.xml", "edit-config")
+            .is_err());
+    }
+
+    #[test]
+    fn parse_capabilities() {
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:netconf:capability:candidate:1.0",
+            "urn:ietf:params:netconf:capability:confirmed-commit:1.1",
+            "urn:ietf:params:netconf:capability:validate:1.1",
+            "urn:ietf:params:netconf:capability:url:1.0?scheme=ftp,file",
+            "urn:ietf:params:netconf:capability:with-defaults:1.0?basic-mode=explicit&also-supported=report-all,trim",
+            "urn:ietf:params:netconf:capability:yang-library:1.1?revision=2019-01-04&content-id=42",
+            "urn:ietf:params:xml:ns:yang:ietf-interfaces?module=ietf-interfaces&revision=2014-05-08&features=arbitrary-names,pre-provisioning&deviations=vendor-dev",
+            "http://example.net/router/2.3/myfeature",
+        ]);
+        assert!(capabilities.base_1_1());
+        assert!(capabilities.candidate());
+        assert!(capabilities.confirmed_commit());
+        assert!(capabilities.confirmed_commit_1_1());
+        assert!(capabilities.validate());
+        assert!(!capabilities.startup());
+        assert!(!capabilities.xpath());
+        assert_eq!(
+            capabilities.url_schemes(),
+            Some(&["ftp".to_string(), "file".to_string()][..])
+        );
+        assert_eq!(
+            capabilities.with_defaults(),
+            Some(&WithDefaults {
+                basic_mode: WithDefaultsMode::Explicit,
+                also_supported: vec![WithDefaultsMode::ReportAll, WithDefaultsMode::Trim],
+            })
+        );
+        assert_eq!(
+            capabilities.yang_library(),
+            Some(&YangLibrary {
+                version: YangLibraryVersion::V1_1,
+                revision: Some("2019-01-04".to_string()),
+                content_id: Some("42".to_string()),
+            })
+        );
+        assert_eq!(
+            capabilities.module("ietf-interfaces"),
+            Some(&Module {
+                namespace: "urn:ietf:params:xml:ns:yang:ietf-interfaces".to_string(),
+                name: "ietf-interfaces".to_string(),
+                revision: Some("2014-05-08".to_string()),
+                features: vec![
+                    "arbitrary-names".to_string(),
+                    "pre-provisioning".to_string()
+                ],
+                deviations: vec!["vendor-dev".to_string()],
+            })
+        );
+        assert!(capabilities.contains(&Capability::Other(
+            "http://example.net/router/2.3/myfeature".to_string()
+        )));
+        assert_eq!(capabilities.uris().len(), 9);
+    }
+
+    #[test]
+    fn check_operations() {
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:netconf:capability:candidate:1.0",
+            "urn:ietf:params:netconf:capability:validate:1.0",
+        ]);
+        assert!(capabilities
+            .check_datastore(&DatastoreType::Candidate, "lock")
+            .is_ok());
+        assert!(capabilities
+            .check_edit_config(
+                &DatastoreType::Candidate,
+                &Some(TestOptionType::TestThenSet),
+                &None
+            )
+            .is_ok());
+        match capabilities.check_copy_config(
+            &DatastoreType::Startup.into(),
+            &CopyConfigSourceType::Datastore {
+                source: DatastoreType::Running,
+            },
+        ) {
+            Err(CapabilityNotSupported {
+                capability,
+                operation,
+            }) => {
+                assert_eq!(capability, "urn:ietf:params:netconf:capability:startup:1.0");
+                assert_eq!(operation, "copy-config");
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(capabilities
+            .check_edit_config(&DatastoreType::Running, &None, &None)
+            .is_err());
+        assert!(capabilities
+            .check_edit_config(
+                &DatastoreType::Candidate,
+                &Some(TestOptionType::TestOnly),
+                &None
+            )
+            .is_err());
+        assert!(capabilities
+            .check_edit_config(
+                &DatastoreType::Candidate,
+                &None,
+                &Some(ErrorOptionType::RollbackOnError)
+            )
+            .is_err());
+        assert!(capabilities.check_create_subscription(&None).is_err());
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:netconf:capability:notification:1.0",
+        ]);
+        match capabilities.check_validate(&ValidateSourceType::Datastore {
+            source: DatastoreType::Running,
+        }) {
+            Err(CapabilityNotSupported {
+                capability,
+                operation,
+            }) => {
+                assert_eq!(capability, "urn:ietf:params:netconf:capability:validate");
+                assert_eq!(operation, "validate");
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(capabilities.check_create_subscription(&None).is_ok());
+        assert!(capabilities
+            .check_create_subscription(&Some(NotificationFilter::XPath {
+                select: "/event".to_string(),
+                namespaces: Default::default(),
+            }))
+            .is_err());
+        assert!(capabilities.check_interleave("close-session").is_ok());
+        assert!(capabilities.check_interleave("get").is_err());
+        assert!(capabilities
+            .check_subscribed_notifications(&None, "establish-subscription")
+            .is_err());
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications?module=ietf-subscribed-notifications&revision=2019-09-09",
+        ]);
+        assert!(capabilities
+            .check_subscribed_notifications(&None, "establish-subscription")
+            .is_ok());
+        let mut subscription = DatastoreSubscription {
+            datastore: "operational".to_string(),
+            filter: None,
+            trigger: UpdateTrigger::Periodic {
+                period: 500,
+                anchor_time: None,
+            },
+            stop_time: None,
+            encoding: None,
+            dscp: None,
+        };
+        assert!(capabilities.check_yang_push(&subscription).is_err());
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications?module=ietf-subscribed-notifications&revision=2019-09-09",
+            "urn:ietf:params:xml:ns:yang:ietf-yang-push?module=ietf-yang-push&revision=2019-09-09",
+        ]);
+        assert!(capabilities.check_yang_push(&subscription).is_ok());
+        subscription.trigger = UpdateTrigger::OnChange {
+            dampening_period: None,
+            sync_on_start: None,
+        };
+        assert!(capabilities.check_yang_push(&subscription).is_err());
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:netconf:capability:yang-library:1.1?revision=2019-01-04&content-id=75",
+        ]);
+        assert!(capabilities.check_yang_push(&subscription).is_ok());
+        assert!(capabilities
+            .check_subscribed_notifications(&None, "establish-subscription")
+            .is_ok());
+        assert!(capabilities
+            .check_get_data(&GetDataOptions::default())
+            .is_ok());
+        assert!(capabilities
+            .check_get_data(&GetDataOptions {
+                with_defaults: Some(WithDefaultsMode::ReportAll),
+                ..Default::default()
+            })
+            .is_err());
+        let capabilities = Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:netconf:capability:with-defaults:1.0?basic-mode=explicit&also-supported=report-all",
+            "urn:ietf:params:xml:ns:yang:ietf-netconf-nmda?module=ietf-netconf-nmda&revision=2019-01-07",
+        ]);
+        let mut options = GetDataOptions {
+            with_defaults: Some(WithDefaultsMode::ReportAll),
+            ..Default::default()
+        };
+        assert!(capabilities.check_get_data(&options).is_ok());
+        options.with_origin = true;
+        assert!(capabilities.check_get_data(&options).is_err());
+        assert!(capabilities
+            .check_with_defaults(Some(WithDefaultsMode::Trim), "get")
+            .is_err());
+        match capabilities.check_monitoring("get-schema") {
+            Err(CapabilityNotSupported {
+                capability,
+                operation,
+            }) => {
+                assert_eq!(capability, consts::MONITORING_XMLNS);
+                assert_eq!(operation, "get-schema");
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(Capabilities::parse(&[
+            "urn:ietf:params:netconf:base:1.1",
+            "urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring?module=ietf-netconf-monitoring&revision=2010-10-04",
+        ])
+        .check_monitoring("get-schema")
+        .is_ok());
+    }
+}
@@ -1,3 +1,10 @@
 pub const XMLNS: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
 pub const SSH_TIMEOUT: u32 = 10000;
 pub const MESSAGE_SEPARATOR: &str = "]]>]]>";
+pub const PARTIAL_LOCK_XMLNS: &str = "urn:ietf:params:xml:ns:netconf:partial-lock:1.0";
+pub const MONITORING_XMLNS: &str = "urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring";
+pub const DATASTORES_XMLNS: &str = "urn:ietf:params:xml:ns:yang:ietf-datastores";
+pub const NMDA_XMLNS: &str = "urn:ietf:params:xml:ns:yang:ietf-netconf-nmda";
+pub const ORIGIN_XMLNS: &str = "urn:ietf:params:xml:ns:yang:ietf-origin";
+pub const WITH_DEFAULTS_XMLNS: &str = "urn:ietf:params:xml:ns:yang:ietf-netconf-with-defaults";
+pub const YANG_XMLNS: &str = "urn:ietf:params:xml:ns:yang:1";
@@ -0,0 +1,455 @@
+use crate::errors::NetconfClientError;
+use crate::models::requests::NotificationFilter;
+use crate::netconf_client::NetconfClient;
+use crate::notification::Notification;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Last processed event of a device stream. Events with the same
+/// `eventTime` are told apart by a digest of their content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub event_time: String,
+    pub event_digests: Vec<u64>,
+}
+
+impl Checkpoint {
+    fn is_processed(&self, notification: &Notification) -> bool {
+        match compare_event_times(&notification.event_time, &self.event_time) {
+            Some(std::cmp::Ordering::Less) => true,
+            Some(std::cmp::Ordering::Equal) => {
+                self.event_digests.contains(&digest(&notification.event))
+            }
+            _ => false,
+        }
+    }
+
+    fn advance(&mut self, notification: &Notification) {
+        match compare_event_times(&notification.event_time, &self.event_time) {
+            Some(std::cmp::Ordering::Equal) => {
+                let digest = digest(&notification.event);
+                if !self.event_digests.contains(&digest) {
+                    self.event_digests.push(digest);
+                }
+            }
+            Some(std::cmp::Ordering::Less) => (),
+            _ => *self = Checkpoint::from(notification),
+        }
+    }
+}
+
+impl From<&Notification> for Checkpoint {
+    fn from(notification: &Notification) -> Self {
+        Checkpoint {
+            event_time: notification.event_time.clone(),
+            event_digests: vec![digest(&notification.event)],
+        }
+    }
+}
+
+/// Checkpoint file with one line per device and stream:
+/// `device<TAB>stream<TAB>eventTime<TAB>digest,digest`.
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    path: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> CheckpointStore {
+        CheckpointStore {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self, device: &str, stream: &str) -> Result<Option<Checkpoint>, io::Error> {
+        Ok(self
+            .read()?
+            .into_iter()
+            .find(|(d, s, _)| d == device && s == stream)
+            .map(|(_, _, checkpoint)| checkpoint))
+    }
+
+    /// Replaces the checkpoint of the device stream. The file is written
+    /// to a temporary file first and renamed, so it is never left partially
+    /// written.
+    pub fn save(
+        &self,
+        device: &str,
+        stream: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<(), io::Error> {
+        if [device, stream, &checkpoint.event_time]
+            .iter()
+            .any(|field| field.contains(['\t', '\n']))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "checkpoint fields must not contain tabs or newlines",
+            ));
+        }
+        let mut checkpoints = self.read()?;
+        checkpoints.retain(|(d, s, _)| d != device || s != stream);
+        checkpoints.push((device.to_string(), stream.to_string(), checkpoint.clone()));
+        let content: String = checkpoints
+            .iter()
+            .map(|(device, stream, checkpoint)| {
+                let digests: Vec<String> = checkpoint
+                    .event_digests
+                    .iter()
+                    .map(|digest| format!("{:016x}", digest))
+                    .collect();
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    device,
+                    stream,
+                    checkpoint.event_time,
+                    digests.join(",")
+                )
+            })
+            .collect();
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(&temporary, &self.path)
+    }
+
+    fn read(&self) -> Result<Vec<(String, String, Checkpoint)>, io::Error> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let device = fields.next()?;
+                let stream = fields.next()?;
+                let event_time = fields.next()?;
+                let event_digests = fields
+                    .next()
+                    .unwrap_or("")
+                    .split(',')
+                    .filter_map(|digest| u64::from_str_radix(digest, 16).ok())
+                    .collect();
+                Some((
+                    device.to_string(),
+                    stream.to_string(),
+                    Checkpoint {
+                        event_time: event_time.to_string(),
+                        event_digests,
+                    },
+                ))
+            })
+            .collect())
+    }
+}
+
+/// Consumes an RFC 5277 event stream without losing events when the session
+/// drops. The client is connected again and the subscription resumes with
+/// `startTime` replay from the checkpoint of the last acknowledged event.
+/// Replayed events which were already acknowledged are skipped, the others
+/// may be delivered again.
+pub struct DurableConsumer {
+    device: String,
+    stream: String,
+    filter: Option<NotificationFilter>,
+    store: CheckpointStore,
+    checkpoint: Option<Checkpoint>,
+    subscribed: bool,
+    reconnect: bool,
+    retry_interval: Duration,
+}
+
+impl DurableConsumer {
+    /// `device` identifies the server in the checkpoint file.
+    pub fn new(
+        device: &str,
+        stream: &str,
+        store: CheckpointStore,
+    ) -> Result<DurableConsumer, NetconfClientError> {
+        let checkpoint = store.load(device, stream)?;
+        Ok(DurableConsumer {
+            device: device.to_string(),
+            stream: stream.to_string(),
+            filter: None,
+            store,
+            checkpoint,
+            subscribed: false,
+            reconnect: false,
+            retry_interval: Duration::from_secs(5),
+        })
+    }
+
+    pub fn with_filter(mut self, filter: NotificationFilter) -> DurableConsumer {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Delay before connecting again after the session dropped.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> DurableConsumer {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    /// Returns the next event which was not acknowledged before, or `None`
+    /// when none arrived before the SSH timeout. Subscribes, and connects
+    /// the client, when needed. After a failed connection attempt, `next`
+    /// can be called again to retry.
+    pub fn next(
+        &mut self,
+        client: &mut NetconfClient,
+    ) -> Result<Option<Notification>, NetconfClientError> {
+        loop {
+            if !self.subscribed {
+                self.subscribe(client)?;
+            }
+            match client.next_notification() {
+                Ok(Some(notification)) => {
+                    if notification.is_replay_complete() {
+                        continue;
+                    }
+                    if notification.is_notification_complete() {
+                        self.subscribed = false;
+                        continue;
+                    }
+                    let processed = self
+                        .checkpoint
+                        .as_ref()
+                        .is_some_and(|checkpoint| checkpoint.is_processed(&notification));
+                    if !processed {
+                        return Ok(Some(notification));
+                    }
+                }
+                Ok(None) => self.subscribed = false,
+                Err(NetconfClientError::IOError(err)) if err.kind() == io::ErrorKind::TimedOut => {
+                    return Ok(None)
+                }
+                Err(err) if is_session_error(&err) => {
+                    self.subscribed = false;
+                    self.reconnect = true;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Records the event as processed in the checkpoint file.
+    pub fn acknowledge(&mut self, notification: &Notification) -> Result<(), NetconfClientError> {
+        match &mut self.checkpoint {
+            Some(checkpoint) => checkpoint.advance(notification),
+            None => self.checkpoint = Some(Checkpoint::from(notification)),
+        }
+        if let Some(checkpoint) = &self.checkpoint {
+            self.store.save(&self.device, &self.stream, checkpoint)?;
+        }
+        Ok(())
+    }
+
+    fn subscribe(&mut self, client: &mut NetconfClient) -> Result<(), NetconfClientError> {
+        if self.reconnect {
+            thread::sleep(self.retry_interval);
+        }
+        if self.reconnect || client.get_session().is_none() {
+            self.reconnect = true;
+            client.connect()?;
+            self.reconnect = false;
+        }
+        let start_time = self
+            .checkpoint
+            .as_ref()
+            .map(|checkpoint| checkpoint.event_time.as_str());
+        let result =
+            client.create_subscription(Some(&self.stream), self.filter.clone(), start_time, None);
+        match result {
+            Ok(_) => {
+                self.subscribed = true;
+                Ok(())
+            }
+            Err(err) => {
+                self.reconnect = is_session_error(&err);
+                Err(err)
+            }
+        }
+    }
+}
+
+fn is_session_error(err: &NetconfClientError) -> bool {
+    matches!(
+        err,
+        NetconfClientError::IOError(_)
+            | NetconfClientError::SSH2Error(_)
+            | NetconfClientError::SSHClientError { .. }
+    )
+}
+
+/// FNV-1a, which unlike the std hasher is stable across releases.
+fn digest(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Compares RFC 3339 date-times, falling back to comparing the text for
+/// values which cannot be parsed.
+fn compare_event_times(a: &str, b: &str) -> Option<std::cmp::Ordering> {
+    match (parse_date_time(a), parse_date_time(b)) {
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        _ if a == b => Some(std::cmp::Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// Seconds since the Unix epoch and nanoseconds.
+fn parse_date_time(text: &str) -> Option<(i64, u32)> {
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = text.get(range)?;
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    let bytes = text.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    let mut rest = &text[19..];
+    let mut nanos = 0u32;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let end = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        let digits = &fraction[..end.min(9)];
+        nanos = format!("{:0<9}", digits).parse().ok()?;
+        rest = &fraction[end..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && &rest[3..4] == ":" => {
+            let hours: i64 = rest[1..3].parse().ok()?;
+            let minutes: i64 = rest[4..6].parse().ok()?;
+            let offset = hours * 3600 + minutes * 60;
+            match &rest[..1] {
+                "+" => offset,
+                "-" => -offset,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let seconds =
+        days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+    Some((seconds, nanos))
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::notification;
+
+    fn event(event_time: &str, value: &str) -> Notification {
+        notification(
+            event_time,
+            &format!(
+                r#"<event xmlns="http://example.com/events"><value>{}</value></event>"#,
+                value
+            ),
+        )
+    }
+
+    #[test]
+    fn event_times() {
+        assert_eq!(parse_date_time("1970-01-01T00:00:00Z"), Some((0, 0)));
+        assert_eq!(
+            parse_date_time("2021-06-01T10:00:00.25Z"),
+            Some((1_622_541_600, 250_000_000))
+        );
+        assert_eq!(
+            parse_date_time("2021-06-01T12:00:00+02:00"),
+            parse_date_time("2021-06-01T10:00:00Z")
+        );
+        assert_eq!(parse_date_time("yesterday"), None);
+        assert_eq!(
+            compare_event_times("2021-06-01T10:00:00.5Z", "2021-06-01T10:00:00Z"),
+            Some(std::cmp::Ordering::Greater)
+        );
+    }
+
+    #[test]
+    fn deduplicate() {
+        let first = event("2021-06-01T10:00:00Z", "1");
+        let mut checkpoint = Checkpoint::from(&first);
+        let second = event("2021-06-01T12:00:00+02:00", "2");
+        assert!(checkpoint.is_processed(&first));
+        assert!(!checkpoint.is_processed(&second));
+        checkpoint.advance(&second);
+        assert!(checkpoint.is_processed(&second));
+        assert_eq!(checkpoint.event_digests.len(), 2);
+
+        let later = event("2021-06-01T10:00:01Z", "3");
+        checkpoint.advance(&later);
+        assert!(checkpoint.is_processed(&first));
+        assert!(!checkpoint.is_processed(&event("2021-06-01T10:00:01Z", "4")));
+        assert_eq!(checkpoint, Checkpoint::from(&later));
+    }
+
+    #[test]
+    fn checkpoint_store() {
+        let path = std::env::temp_dir().join(format!(
+            "netconf-client-checkpoints-{}/checkpoints",
+            std::process::id()
+        ));
+        let store = CheckpointStore::new(&path);
+        assert_eq!(store.load("router1", "NETCONF").unwrap(), None);
+
+        let checkpoint = Checkpoint::from(&event("2021-06-01T10:00:00Z", "1"));
+        store.save("router1", "NETCONF", &checkpoint).unwrap();
+        store.save("router2", "NETCONF", &checkpoint).unwrap();
+        let later = Checkpoint::from(&event("2021-06-01T11:00:00Z", "2"));
+        store.save("router1", "NETCONF", &later).unwrap();
+        assert_eq!(store.load("router1", "NETCONF").unwrap(), Some(later));
+        assert_eq!(store.load("router2", "NETCONF").unwrap(), Some(checkpoint));
+        assert!(store
+            .save(
+                "router\t1",
+                "NETCONF",
+                &Checkpoint::from(&event("2021-06-01T10:00:00Z", "1"))
+            )
+            .is_err());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -8,6 +8,10 @@ pub enum NetconfClientError {
     AddrParseError(#[from] std::net::AddrParseError),
     #[error(transparent)]
     SSH2Error(#[from] ssh2::Error),
+    #[error(transparent)]
+    DeserializeError(#[from] quick_xml::DeError),
+    #[error(transparent)]
+    XMLError(#[from] quick_xml::Error),
     #[error("Netconf error response {err:?}")]
     NetconfError {
         err: Vec<crate::models::replies::RpcError>,
@@ -16,4 +20,9 @@ pub enum NetconfClientError {
     SSHClientError { err: String },
     #[error("Wrong response id {err:?}")]
     NetconfResponseIdError { err: String },
+    #[error("Capability {capability:?} required by {operation:?} not supported by server")]
+    CapabilityNotSupported {
+        capability: String,
+        operation: String,
+    },
 }
@@ -0,0 +1,145 @@
+use crate::consts::MESSAGE_SEPARATOR;
+use crate::session::Framing;
+
+const END_OF_CHUNKS: &[u8] = b"\n##\n";
+const MAX_CHUNK_HEADER: usize = 13;
+
+pub(crate) fn encode(message: &str, framing: Framing) -> String {
+    match framing {
+        Framing::EndOfMessage => message.to_string() + MESSAGE_SEPARATOR,
+        Framing::Chunked => format!("\n#{}\n{}\n##\n", message.len(), message),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct MessageDecoder {
+    buffer: Vec<u8>,
+    framing: Framing,
+}
+
+impl Default for MessageDecoder {
+    fn default() -> Self {
+        MessageDecoder {
+            buffer: Vec::new(),
+            framing: Framing::EndOfMessage,
+        }
+    }
+}
+
+impl MessageDecoder {
+    pub fn set_framing(&mut self, framing: Framing) {
+        self.framing = framing;
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    pub fn next_message(&mut self) -> Option<String> {
+        match self.framing {
+            Framing::EndOfMessage => self.next_eom_message(),
+            Framing::Chunked => self.next_chunked_message(),
+        }
+    }
+
+    fn next_eom_message(&mut self) -> Option<String> {
+        let separator = MESSAGE_SEPARATOR.as_bytes();
+        let end = self
+            .buffer
+            .windows(separator.len())
+            .position(|window| window == separator)?;
+        let message = String::from_utf8_lossy(&self.buffer[..end])
+            .trim()
+            .to_string();
+        self.buffer.drain(..end + separator.len());
+        Some(message)
+    }
+
+    fn next_chunked_message(&mut self) -> Option<String> {
+        let start = self.buffer.iter().position(|b| !b.is_ascii_whitespace())?;
+        let mut position = start.saturating_sub(1);
+        let mut message = Vec::new();
+        loop {
+            let rest = &self.buffer[position..];
+            if rest.len() < END_OF_CHUNKS.len() {
+                return None;
+            }
+            if rest.starts_with(END_OF_CHUNKS) {
+                position += END_OF_CHUNKS.len();
+                if message.is_empty() {
+                    self.buffer.drain(..position);
+                    return self.next_chunked_message();
+                }
+                break;
+            }
+            if !rest.starts_with(b"\n#") {
+                // Not a chunk header, skip the byte so the stream can resynchronise.
+                self.buffer.drain(..position + 1);
+                return self.next_chunked_message();
+            }
+            let header_end = match rest[2..].iter().position(|b| *b == b'\n') {
+                Some(header_end) => header_end + 2,
+                None if rest.len() > MAX_CHUNK_HEADER => {
+                    self.buffer.drain(..position + 1);
+                    return self.next_chunked_message();
+                }
+                None => return None,
+            };
+            let size = match std::str::from_utf8(&rest[2..header_end])
+                .ok()
+                .and_then(|size| size.parse::<usize>().ok())
+            {
+                Some(size) => size,
+                None => {
+                    self.buffer.drain(..position + 1);
+                    return self.next_chunked_message();
+                }
+            };
+            if rest.len() < header_end + 1 + size {
+                return None;
+            }
+            message.extend_from_slice(&rest[header_end + 1..header_end + 1 + size]);
+            position += header_end + 1 + size;
+        }
+        self.buffer.drain(..position);
+        Some(String::from_utf8_lossy(&message).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_split_messages() {
+        let mut decoder = MessageDecoder::default();
+        decoder.push(b"<rpc-reply message-id=\"1\"><ok/></rpc-reply>]]>");
+        assert_eq!(decoder.next_message(), None);
+        decoder.push(b"]]><rpc-reply message-id=\"2\"><ok/></rpc-reply>]]>]]>\n");
+        assert_eq!(
+            decoder.next_message(),
+            Some("<rpc-reply message-id=\"1\"><ok/></rpc-reply>".to_string())
+        );
+        assert_eq!(
+            decoder.next_message(),
+            Some("<rpc-reply message-id=\"2\"><ok/></rpc-reply>".to_string())
+        );
+        assert_eq!(decoder.next_message(), None);
+    }
+
+    #[test]
+    fn encode_decode_chunked() {
+        let message = "<rpc-reply message-id=\"1\"><ok/></rpc-reply>";
+        let mut decoder = MessageDecoder::default();
+        decoder.set_framing(Framing::Chunked);
+        decoder.push(encode(message, Framing::Chunked).as_bytes());
+        decoder.push(b"\n#12\n<rpc-reply m");
+        decoder.push(b"\n#31\nessage-id=\"2\"><ok/></rpc-reply>\n##\n");
+        assert_eq!(decoder.next_message(), Some(message.to_string()));
+        assert_eq!(
+            decoder.next_message(),
+            Some("<rpc-reply message-id=\"2\"><ok/></rpc-reply>".to_string())
+        );
+        assert_eq!(decoder.next_message(), None);
+    }
+}
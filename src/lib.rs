@@ -1,5 +1,18 @@
+pub mod async_netconf_client;
+pub mod async_ssh_client;
+pub mod capabilities;
 mod consts;
+pub mod consumer;
 pub mod errors;
+mod framing;
+mod messages;
+pub mod mirror;
 pub mod models;
 pub mod netconf_client;
+pub mod notification;
+pub mod observer;
+pub mod schema_cache;
+pub mod session;
+pub mod shared_netconf_client;
 pub mod ssh_client;
+pub mod xml;
@@ -0,0 +1,1496 @@
+use crate::capabilities::WithDefaultsMode;
+use crate::consts;
+use crate::errors::NetconfClientError;
+use crate::errors::NetconfClientError::{NetconfError, NetconfResponseIdError};
+use crate::models::subscribed_notifications::{SubscriptionId, SUBSCRIBED_NOTIFICATIONS_NAMESPACE};
+use crate::models::yang_library::YANG_LIBRARY_NAMESPACE;
+use crate::models::yang_push::YANG_PUSH_NAMESPACE;
+use crate::models::{replies::*, requests::*};
+use crate::notification::NOTIFICATION_XMLNS;
+use crate::xml::Element;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::se::to_string;
+use quick_xml::Reader;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub(crate) fn hello(capabilities: &[String]) -> String {
+    let req = HelloClient {
+        xmlns: consts::XMLNS.to_string(),
+        capabilities: CapabilitiesClient {
+            capabilities: capabilities
+                .iter()
+                .map(|capability| Capability {
+                    capability: capability.clone(),
+                })
+                .collect(),
+        },
+    };
+    to_string(&req).unwrap()
+}
+
+pub(crate) fn kill_session(message_id: u32, session_id: u32) -> String {
+    let req = KillSessionReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        kill_session: KillSession {
+            session_id: SessionId { value: session_id },
+        },
+    };
+    to_string(&req).unwrap()
+}
+
+pub(crate) fn close_session(message_id: u32) -> String {
+    let req = CloseSessionReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        close_session: Default::default(),
+    };
+    to_string(&req).unwrap()
+}
+
+pub(crate) fn get_config(
+    message_id: u32,
+    source: DatastoreType,
+    filter: Option<Filter>,
+    with_defaults: Option<WithDefaultsMode>,
+) -> String {
+    let mut filter_copy = filter.clone();
+    let filter_exists = filter.is_some();
+    if filter_exists {
+        filter_copy.as_mut().unwrap().data = " ".to_string();
+    }
+    let req = GetConfigReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        get_config: GetConfig {
+            source: Target { target: source },
+            filter: filter_copy,
+            with_defaults: with_defaults.map(Into::into),
+        },
+    };
+    let mut cmd = to_string(&req).unwrap();
+    if let Some(filter) = filter {
+        insert_get_filter(&mut cmd, filter);
+    }
+    cmd
+}
+
+pub(crate) fn get(
+    message_id: u32,
+    filter: Option<Filter>,
+    with_defaults: Option<WithDefaultsMode>,
+) -> String {
+    let filter_exists = filter.is_some();
+    let mut filter_copy = filter.clone();
+    if filter_exists {
+        filter_copy.as_mut().unwrap().data = " ".to_string();
+    }
+    let req = GetReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        get: Get {
+            filter: filter_copy,
+            with_defaults: with_defaults.map(Into::into),
+        },
+    };
+
+    // hack
+    let mut cmd = to_string(&req).unwrap();
+    if let Some(filter) = filter {
+        insert_get_filter(&mut cmd, filter);
+    }
+    cmd
+}
+
+// hack
+fn insert_get_filter(cmd: &mut String, filter: Filter) {
+    cmd.insert_str(cmd.rfind(" </filter>").unwrap(), &filter.data);
+    cmd.insert_str(
+        cmd.find("<filter").unwrap() + "<filter".len(),
+        &namespace_declarations(&filter.namespaces),
+    );
+}
+
+pub(crate) fn edit_config(
+    message_id: u32,
+    target: DatastoreType,
+    data: String,
+    default_operation: Option<DefaultOperationType>,
+    test_option: Option<TestOptionType>,
+    error_option: Option<ErrorOptionType>,
+) -> String {
+    let config = Data {
+        xmlns_xc: Some("urn:ietf:params:xml:ns:netconf:base:1.0".to_string()),
+        data: " ".to_string(),
+    };
+    let req = edit_config_req(
+        message_id,
+        target,
+        Some(config),
+        None,
+        default_operation,
+        test_option,
+        error_option,
+    );
+    let mut cmd = to_string(&req).unwrap();
+    // hack
+    cmd.insert_str(cmd.rfind(" </config>").unwrap(), &data);
+    cmd
+}
+
+pub(crate) fn edit_config_url(
+    message_id: u32,
+    target: DatastoreType,
+    url: &str,
+    default_operation: Option<DefaultOperationType>,
+    test_option: Option<TestOptionType>,
+    error_option: Option<ErrorOptionType>,
+) -> String {
+    let req = edit_config_req(
+        message_id,
+        target,
+        None,
+        Some(Url::new(url)),
+        default_operation,
+        test_option,
+        error_option,
+    );
+    to_string(&req).unwrap()
+}
+
+fn edit_config_req(
+    message_id: u32,
+    target: DatastoreType,
+    config: Option<Data>,
+    url: Option<Url>,
+    default_operation: Option<DefaultOperationType>,
+    test_option: Option<TestOptionType>,
+    error_option: Option<ErrorOptionType>,
+) -> EditConfigReq {
+    let mut req = EditConfigReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        edit_config: EditConfig {
+            target: Target { target },
+            default_operation: None,
+            test_option: None,
+            error_option: None,
+            config,
+            url,
+        },
+    };
+    if let Some(default_operation) = default_operation {
+        req.edit_config.default_operation = Some(DefaultOperation {
+            value: default_operation,
+        })
+    }
+    if let Some(test_option) = test_option {
+        req.edit_config.test_option = Some(TestOption { value: test_option })
+    }
+    if let Some(error_option) = error_option {
+        req.edit_config.error_option = Some(ErrorOption {
+            value: error_option,
+        })
+    }
+    req
+}
+
+pub(crate) fn lock(message_id: u32, target: DatastoreType) -> String {
+    let model = LockReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        lock: Lock {
+            target: Target { target },
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn unlock(message_id: u32, target: DatastoreType) -> String {
+    let model = UnlockReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        unlock: Unlock {
+            target: Target { target },
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn delete_config(message_id: u32, target: ConfigTargetType) -> String {
+    let model = DeleteConfigReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        delete_config: DeleteConfig { target },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn discard_changes(message_id: u32) -> String {
+    let model = DiscardChangesReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        discard_changes: Default::default(),
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn commit(message_id: u32, commit: Commit) -> String {
+    let model = CommitReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        commit,
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn confirmed_commit(
+    message_id: u32,
+    confirm_timeout: Option<u32>,
+    persist: Option<String>,
+) -> String {
+    commit(
+        message_id,
+        Commit {
+            confirmed: Some(Confirmed {}),
+            confirm_timeout: confirm_timeout.map(|value| ConfirmTimeout { value }),
+            persist: persist.map(|value| Persist { value }),
+            persist_id: None,
+        },
+    )
+}
+
+pub(crate) fn confirm_commit(message_id: u32, persist_id: Option<String>) -> String {
+    commit(
+        message_id,
+        Commit {
+            persist_id: persist_id.map(|value| PersistId { value }),
+            ..Default::default()
+        },
+    )
+}
+
+pub(crate) fn cancel_commit(message_id: u32, persist_id: Option<String>) -> String {
+    let model = CancelCommitReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        cancel_commit: CancelCommit {
+            persist_id: persist_id.map(|value| PersistId { value }),
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn copy_config(
+    message_id: u32,
+    target: ConfigTargetType,
+    source: CopyConfigSourceType,
+    with_defaults: Option<WithDefaultsMode>,
+) -> String {
+    let model = CopyConfigReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        copy_config: CopyConfig {
+            target,
+            source,
+            with_defaults: with_defaults.map(Into::into),
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn partial_lock(
+    message_id: u32,
+    select: Vec<String>,
+    namespaces: &BTreeMap<String, String>,
+) -> String {
+    let model = PartialLockReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        partial_lock: PartialLock {
+            xmlns: consts::PARTIAL_LOCK_XMLNS.to_string(),
+            select: select.into_iter().map(|value| Select { value }).collect(),
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    // hack
+    let declarations = namespace_declarations(namespaces);
+    cmd.insert_str(
+        cmd.find("<partial-lock ").unwrap() + "<partial-lock".len(),
+        &declarations,
+    );
+    cmd
+}
+
+pub(crate) fn partial_unlock(message_id: u32, lock_id: u32) -> String {
+    let model = PartialUnlockReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        partial_unlock: PartialUnlock {
+            xmlns: consts::PARTIAL_LOCK_XMLNS.to_string(),
+            lock_id: LockId { value: lock_id },
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn get_schema(
+    message_id: u32,
+    identifier: &str,
+    version: Option<&str>,
+    format: Option<SchemaFormat>,
+) -> String {
+    let model = GetSchemaReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        get_schema: GetSchema {
+            xmlns: consts::MONITORING_XMLNS.to_string(),
+            identifier: Identifier {
+                value: identifier.to_string(),
+            },
+            version: version.map(|value| Version {
+                value: value.to_string(),
+            }),
+            format: format.map(|value| Format { value }),
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn validate(message_id: u32, source: ValidateSourceType) -> String {
+    let (source, config) = match source {
+        ValidateSourceType::Config { config } => (
+            ValidateSourceType::Config {
+                config: Data {
+                    xmlns_xc: config.xmlns_xc,
+                    data: " ".to_string(),
+                },
+            },
+            Some(config.data),
+        ),
+        source => (source, None),
+    };
+    let model = ValidateReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        validate: Validate { source },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    if let Some(config) = config {
+        // hack
+        cmd.insert_str(cmd.rfind(" </config>").unwrap(), &config);
+    }
+    cmd
+}
+
+pub(crate) fn raw_rpc(message_id: u32, data: &str) -> String {
+    let model = RawRpcReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        data: " ".to_string(),
+    };
+    let mut cmd = to_string(&model).unwrap();
+    // hack
+    cmd.insert_str(cmd.rfind(" </rpc>").unwrap(), data);
+    cmd
+}
+
+/// Input element of a module-defined RPC, named after the serialized type and
+/// placed in `namespace`. It is sent with `raw_rpc`.
+pub(crate) fn rpc_input<T: Serialize>(
+    namespace: &str,
+    input: &T,
+) -> Result<String, NetconfClientError> {
+    let mut data = to_string(input)?;
+    // hack
+    let name_end = data
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(data.len());
+    data.insert_str(
+        name_end,
+        &format!(
+            r#" xmlns="{}""#,
+            String::from_utf8_lossy(&escape(namespace.as_bytes()))
+        ),
+    );
+    Ok(data)
+}
+
+/// YANG 1.1 `<action>` with `input` nested in the data nodes of `path`,
+/// sent with `raw_rpc`.
+pub(crate) fn action_input<T: Serialize>(
+    path: &ActionPath,
+    input: &T,
+) -> Result<String, NetconfClientError> {
+    let mut data = rpc_input(path.namespace(), input)?;
+    for node in path.nodes.iter().rev() {
+        let declaration = node
+            .namespace
+            .as_ref()
+            .map(|namespace| {
+                format!(
+                    r#" xmlns="{}""#,
+                    String::from_utf8_lossy(&escape(namespace.as_bytes()))
+                )
+            })
+            .unwrap_or_default();
+        let keys: String = node
+            .keys
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "<{}>{}</{}>",
+                    key,
+                    String::from_utf8_lossy(&escape(value.as_bytes())),
+                    key
+                )
+            })
+            .collect();
+        data = format!(
+            "<{}{}>{}{}</{}>",
+            node.name, declaration, keys, data, node.name
+        );
+    }
+    Ok(format!(
+        r#"<action xmlns="{}">{}</action>"#,
+        consts::YANG_XMLNS,
+        data
+    ))
+}
+
+pub(crate) fn create_subscription(
+    message_id: u32,
+    stream: Option<&str>,
+    filter: Option<NotificationFilter>,
+    start_time: Option<&str>,
+    stop_time: Option<&str>,
+) -> String {
+    let subscription_filter = filter.as_ref().map(|filter| match filter {
+        NotificationFilter::Subtree(_) => SubscriptionFilter {
+            filter_type: "subtree".to_string(),
+            select: None,
+            data: " ".to_string(),
+        },
+        NotificationFilter::XPath { select, .. } => SubscriptionFilter {
+            filter_type: "xpath".to_string(),
+            select: Some(select.clone()),
+            data: " ".to_string(),
+        },
+    });
+    let model = CreateSubscriptionReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        create_subscription: CreateSubscription {
+            xmlns: NOTIFICATION_XMLNS.to_string(),
+            stream: stream.map(|value| Stream {
+                value: value.to_string(),
+            }),
+            filter: subscription_filter,
+            start_time: start_time.map(|value| StartTime {
+                value: value.to_string(),
+            }),
+            stop_time: stop_time.map(|value| StopTime {
+                value: value.to_string(),
+            }),
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    if let Some(filter) = filter {
+        insert_filter(&mut cmd, "filter", filter);
+    }
+    cmd
+}
+
+// hack
+fn insert_filter(cmd: &mut String, element: &str, filter: NotificationFilter) {
+    match filter {
+        NotificationFilter::Subtree(data) => {
+            cmd.insert_str(cmd.rfind(&format!(" </{}>", element)).unwrap(), &data);
+        }
+        NotificationFilter::XPath { namespaces, .. } => {
+            cmd.insert_str(
+                cmd.find(&format!("<{}", element)).unwrap() + element.len() + 1,
+                &namespace_declarations(&namespaces),
+            );
+        }
+    }
+}
+
+fn stream_filters(
+    filter: &Option<NotificationFilter>,
+) -> (Option<StreamFilter>, Option<StreamFilter>) {
+    match filter {
+        Some(NotificationFilter::Subtree(_)) => (
+            Some(StreamFilter {
+                data: " ".to_string(),
+            }),
+            None,
+        ),
+        Some(NotificationFilter::XPath { select, .. }) => (
+            None,
+            Some(StreamFilter {
+                data: select.clone(),
+            }),
+        ),
+        None => (None, None),
+    }
+}
+
+fn insert_stream_filter(cmd: &mut String, filter: Option<NotificationFilter>) {
+    match filter {
+        Some(filter @ NotificationFilter::Subtree(_)) => {
+            insert_filter(cmd, "stream-subtree-filter", filter)
+        }
+        Some(filter @ NotificationFilter::XPath { .. }) => {
+            insert_filter(cmd, "stream-xpath-filter", filter)
+        }
+        None => (),
+    }
+}
+
+pub(crate) fn establish_subscription(message_id: u32, subscription: StreamSubscription) -> String {
+    let (stream_subtree_filter, stream_xpath_filter) = stream_filters(&subscription.filter);
+    let model = EstablishSubscriptionReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        establish_subscription: EstablishSubscription {
+            xmlns: SUBSCRIBED_NOTIFICATIONS_NAMESPACE.to_string(),
+            xmlns_yp: None,
+            stream: Some(Stream {
+                value: subscription.stream,
+            }),
+            stream_subtree_filter,
+            stream_xpath_filter,
+            replay_start_time: subscription
+                .replay_start_time
+                .map(|value| ReplayStartTime { value }),
+            datastore: None,
+            datastore_subtree_filter: None,
+            datastore_xpath_filter: None,
+            periodic: None,
+            on_change: None,
+            stop_time: subscription.stop_time.map(|value| StopTime { value }),
+            dscp: subscription.dscp.map(|value| Dscp { value }),
+            encoding: subscription.encoding.map(|value| Encoding { value }),
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    insert_stream_filter(&mut cmd, subscription.filter);
+    cmd
+}
+
+pub(crate) fn establish_datastore_subscription(
+    message_id: u32,
+    subscription: DatastoreSubscription,
+) -> String {
+    let (datastore_subtree_filter, datastore_xpath_filter) = stream_filters(&subscription.filter);
+    let (periodic, on_change) = match subscription.trigger {
+        UpdateTrigger::Periodic {
+            period,
+            anchor_time,
+        } => (
+            Some(Periodic {
+                period: Period { value: period },
+                anchor_time: anchor_time.map(|value| AnchorTime { value }),
+            }),
+            None,
+        ),
+        UpdateTrigger::OnChange {
+            dampening_period,
+            sync_on_start,
+        } => (
+            None,
+            Some(OnChange {
+                dampening_period: dampening_period.map(|value| DampeningPeriod { value }),
+                sync_on_start: sync_on_start.map(|value| SyncOnStart { value }),
+            }),
+        ),
+    };
+    let model = EstablishSubscriptionReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        establish_subscription: EstablishSubscription {
+            xmlns: SUBSCRIBED_NOTIFICATIONS_NAMESPACE.to_string(),
+            xmlns_yp: Some(YANG_PUSH_NAMESPACE.to_string()),
+            stream: None,
+            stream_subtree_filter: None,
+            stream_xpath_filter: None,
+            replay_start_time: None,
+            datastore: Some(PushDatastore {
+                xmlns_ds: consts::DATASTORES_XMLNS.to_string(),
+                value: format!("ds:{}", subscription.datastore),
+            }),
+            datastore_subtree_filter,
+            datastore_xpath_filter,
+            periodic,
+            on_change,
+            stop_time: subscription.stop_time.map(|value| StopTime { value }),
+            dscp: subscription.dscp.map(|value| Dscp { value }),
+            encoding: subscription.encoding.map(|value| Encoding { value }),
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    match subscription.filter {
+        Some(filter @ NotificationFilter::Subtree(_)) => {
+            insert_filter(&mut cmd, "yp:datastore-subtree-filter", filter)
+        }
+        Some(filter @ NotificationFilter::XPath { .. }) => {
+            insert_filter(&mut cmd, "yp:datastore-xpath-filter", filter)
+        }
+        None => (),
+    }
+    cmd
+}
+
+pub(crate) fn modify_subscription(
+    message_id: u32,
+    id: SubscriptionId,
+    filter: Option<NotificationFilter>,
+    stop_time: Option<&str>,
+) -> String {
+    let (stream_subtree_filter, stream_xpath_filter) = stream_filters(&filter);
+    let model = ModifySubscriptionReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        modify_subscription: ModifySubscription {
+            xmlns: SUBSCRIBED_NOTIFICATIONS_NAMESPACE.to_string(),
+            id: Id { value: id.0 },
+            stream_subtree_filter,
+            stream_xpath_filter,
+            stop_time: stop_time.map(|value| StopTime {
+                value: value.to_string(),
+            }),
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    insert_stream_filter(&mut cmd, filter);
+    cmd
+}
+
+pub(crate) fn delete_subscription(message_id: u32, id: SubscriptionId) -> String {
+    let model = DeleteSubscriptionReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        delete_subscription: SubscriptionRef {
+            xmlns: SUBSCRIBED_NOTIFICATIONS_NAMESPACE.to_string(),
+            id: Id { value: id.0 },
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn kill_subscription(message_id: u32, id: SubscriptionId) -> String {
+    let model = KillSubscriptionReq {
+        xmlns: consts::XMLNS.to_string(),
+        message_id,
+        kill_subscription: SubscriptionRef {
+            xmlns: SUBSCRIBED_NOTIFICATIONS_NAMESPACE.to_string(),
+            id: Id { value: id.0 },
+        },
+    };
+    to_string(&model).unwrap()
+}
+
+pub(crate) fn nmda_get_data(
+    message_id: u32,
+    datastore: Datastore,
+    options: GetDataOptions,
+) -> String {
+    let (subtree_filter, xpath_filter) = match &options.filter {
+        Some(Filter {
+            filter_type: FilterType::Subtree,
+            ..
+        }) => (
+            Some(StreamFilter {
+                data: " ".to_string(),
+            }),
+            None,
+        ),
+        Some(Filter {
+            filter_type: FilterType::XPath,
+            select,
+            ..
+        }) => (
+            None,
+            Some(StreamFilter {
+                data: select.clone().unwrap_or_default(),
+            }),
+        ),
+        None => (None, None),
+    };
+    let (origin_filter, negated_origin_filter) = match options.origin_filter {
+        Some(OriginFilter::Include(origins)) => (origins, Vec::new()),
+        Some(OriginFilter::Exclude(origins)) => (Vec::new(), origins),
+        None => (Vec::new(), Vec::new()),
+    };
+    let model = GetDataReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        get_data: GetData {
+            xmlns: consts::NMDA_XMLNS.to_string(),
+            datastore: datastore.into(),
+            subtree_filter,
+            xpath_filter,
+            config_filter: options.config_filter.map(|value| ConfigFilter { value }),
+            origin_filter: origin_filter.into_iter().map(Into::into).collect(),
+            negated_origin_filter: negated_origin_filter.into_iter().map(Into::into).collect(),
+            max_depth: options.max_depth.map(|value| MaxDepth { value }),
+            with_origin: if options.with_origin {
+                Some(WithOrigin {})
+            } else {
+                None
+            },
+            with_defaults: options.with_defaults.map(Into::into),
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    if let Some(filter) = options.filter {
+        insert_data_filter(&mut cmd, filter);
+    }
+    cmd
+}
+
+// hack
+fn insert_data_filter(cmd: &mut String, filter: Filter) {
+    let element = match filter.filter_type {
+        FilterType::Subtree => {
+            cmd.insert_str(cmd.rfind(" </subtree-filter>").unwrap(), &filter.data);
+            "<subtree-filter"
+        }
+        FilterType::XPath => "<xpath-filter",
+    };
+    cmd.insert_str(
+        cmd.find(element).unwrap() + element.len(),
+        &namespace_declarations(&filter.namespaces),
+    );
+}
+
+pub(crate) fn nmda_edit_data(
+    message_id: u32,
+    datastore: Datastore,
+    data: String,
+    default_operation: Option<DefaultOperationType>,
+) -> String {
+    let model = EditDataReq {
+        message_id,
+        xmlns: consts::XMLNS.to_string(),
+        edit_data: EditData {
+            xmlns: consts::NMDA_XMLNS.to_string(),
+            datastore: datastore.into(),
+            default_operation: default_operation.map(|value| DefaultOperation { value }),
+            config: Data {
+                xmlns_xc: Some(consts::XMLNS.to_string()),
+                data: " ".to_string(),
+            },
+        },
+    };
+    let mut cmd = to_string(&model).unwrap();
+    // hack
+    cmd.insert_str(cmd.rfind(" </config>").unwrap(), &data);
+    cmd
+}
+
+pub(crate) fn get_modules_state(message_id: u32) -> String {
+    get(
+        message_id,
+        Some(Filter::subtree(&format!(
+            r#"<modules-state xmlns="{}"/>"#,
+            YANG_LIBRARY_NAMESPACE
+        ))),
+        None,
+    )
+}
+
+pub(crate) fn get_yang_library(message_id: u32) -> String {
+    get(
+        message_id,
+        Some(Filter::subtree(&format!(
+            r#"<yang-library xmlns="{}"/>"#,
+            YANG_LIBRARY_NAMESPACE
+        ))),
+        None,
+    )
+}
+
+fn namespace_declarations(namespaces: &BTreeMap<String, String>) -> String {
+    namespaces
+        .iter()
+        .map(|(prefix, namespace)| {
+            format!(
+                r#" xmlns:{}="{}""#,
+                prefix,
+                String::from_utf8_lossy(&escape(namespace.as_bytes()))
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn get_netconf_state(message_id: u32, container: Option<&str>) -> String {
+    let data = match container {
+        Some(container) => format!(
+            r#"<netconf-state xmlns="{}"><{}/></netconf-state>"#,
+            consts::MONITORING_XMLNS,
+            container
+        ),
+        None => format!(r#"<netconf-state xmlns="{}"/>"#, consts::MONITORING_XMLNS),
+    };
+    get(message_id, Some(Filter::subtree(&data)), None)
+}
+
+pub(crate) fn parse_hello(raw: &str) -> Result<HelloServer, NetconfClientError> {
+    let reply: HelloServer = quick_xml::de::from_str(raw)?;
+    make_return(reply)
+}
+
+pub(crate) fn parse_reply<T: RpcRsp + DeserializeOwned>(
+    raw: &str,
+    message_id: u32,
+) -> Result<T, NetconfClientError> {
+    let reply: T = quick_xml::de::from_str(raw)?;
+    check_response_message_id(&reply, message_id)?;
+    make_return(reply)
+}
+
+pub(crate) fn parse_get_reply(
+    raw: &str,
+    message_id: u32,
+    with_defaults: Option<WithDefaultsMode>,
+) -> Result<GetRsp, NetconfClientError> {
+    let mut reply: GetRsp = parse_reply(raw, message_id)?;
+    reply.data = Some(data_content(raw).unwrap_or("").to_string());
+    reply.data_nodes = tagged_data_nodes(raw, with_defaults)?;
+    Ok(reply)
+}
+
+pub(crate) fn parse_get_config_reply(
+    raw: &str,
+    message_id: u32,
+    with_defaults: Option<WithDefaultsMode>,
+) -> Result<GetConfigRsp, NetconfClientError> {
+    let mut reply: GetConfigRsp = parse_reply(raw, message_id)?;
+    reply.data = Some(data_content(raw).unwrap_or("").to_string());
+    reply.data_nodes = tagged_data_nodes(raw, with_defaults)?;
+    Ok(reply)
+}
+
+pub(crate) fn parse_get_data_reply(
+    raw: &str,
+    message_id: u32,
+    with_defaults: Option<WithDefaultsMode>,
+) -> Result<GetDataRsp, NetconfClientError> {
+    let mut reply: GetDataRsp = parse_reply(raw, message_id)?;
+    reply.data = Some(data_content(raw).unwrap_or("").to_string());
+    reply.data_nodes = tagged_data_nodes(raw, with_defaults)?;
+    Ok(reply)
+}
+
+/// Data nodes of a reply requested in the `report-all-tagged` mode, parsed
+/// from the whole reply so that the `wd` prefix declaration is in scope.
+fn tagged_data_nodes(
+    raw: &str,
+    with_defaults: Option<WithDefaultsMode>,
+) -> Result<Option<Vec<Element>>, NetconfClientError> {
+    if with_defaults != Some(WithDefaultsMode::ReportAllTagged) {
+        return Ok(None);
+    }
+    let reply = Element::parse(raw)?;
+    Ok(Some(
+        reply
+            .child("data")
+            .map(|data| data.children.iter().map(Element::detached).collect())
+            .unwrap_or_default(),
+    ))
+}
+
+pub(crate) fn parse_data_reply<T: DeserializeOwned>(
+    raw: &str,
+    message_id: u32,
+) -> Result<T, NetconfClientError> {
+    let reply = parse_get_reply(raw, message_id, None)?;
+    Ok(quick_xml::de::from_str(
+        reply.data.as_deref().unwrap_or(""),
+    )?)
+}
+
+pub(crate) fn parse_get_schema_reply(
+    raw: &str,
+    message_id: u32,
+) -> Result<GetSchemaRsp, NetconfClientError> {
+    let mut reply: GetSchemaRsp = parse_reply(raw, message_id)?;
+    reply.schema = schema_text(data_content(raw).unwrap_or(""));
+    Ok(reply)
+}
+
+/// YANG text is sent escaped or in CDATA sections, YIN is sent as XML
+/// elements which are returned unchanged.
+fn schema_text(data: &str) -> String {
+    let mut reader = Reader::from_str(data);
+    let mut buf = Vec::new();
+    let mut text = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Text(e)) | Ok(Event::CData(e)) => match e.unescaped() {
+                Ok(unescaped) => text.extend_from_slice(&unescaped),
+                Err(_) => text.extend_from_slice(e.escaped()),
+            },
+            Ok(Event::Eof) => break,
+            _ => return data.trim().to_string(),
+        }
+        buf.clear();
+    }
+    String::from_utf8_lossy(&text).trim().to_string()
+}
+
+pub(crate) fn parse_raw_rpc_reply(
+    raw: &str,
+    message_id: u32,
+) -> Result<RawRpcRsp, NetconfClientError> {
+    let mut reply: RawRpcRsp = parse_reply(raw, message_id)?;
+    reply.reply = raw.to_string();
+    Ok(reply)
+}
+
+/// Output leaves of a module-defined RPC, after `<rpc-error>` has been checked.
+pub(crate) fn parse_rpc_reply<T: DeserializeOwned>(
+    raw: &str,
+    message_id: u32,
+) -> Result<T, NetconfClientError> {
+    parse_reply::<RawRpcRsp>(raw, message_id)?;
+    Ok(quick_xml::de::from_str(raw)?)
+}
+
+pub(crate) fn data_content(text: &str) -> Option<&str> {
+    let value_begin = text.find("<data")?;
+    let value_end = text[value_begin..].find('>').map(|i| i + value_begin)?;
+    let end_element = text.rfind("</data>")?;
+    if value_end + 1 > end_element {
+        return None;
+    }
+    Some(&text[value_end + 1..end_element])
+}
+
+/// Error for an `<rpc-reply>` which matches no pending request.
+pub(crate) fn unmatched_reply_error(raw: &str) -> NetconfClientError {
+    match quick_xml::de::from_str::<UnmatchedRpcRsp>(raw) {
+        Ok(UnmatchedRpcRsp {
+            rpc_error: Some(err),
+            ..
+        }) => NetconfError { err },
+        Ok(UnmatchedRpcRsp { message_id, .. }) => NetconfResponseIdError {
+            err: format!("no request for response id: {:?}", message_id),
+        },
+        Err(err) => err.into(),
+    }
+}
+
+pub(crate) fn reply_message_id(raw: &str) -> Option<u32> {
+    root_element(raw)?.1
+}
+
+pub(crate) fn is_notification(raw: &str) -> bool {
+    matches!(root_element(raw), Some((name, _)) if name == "notification")
+}
+
+/// Name of the operation element of an `<rpc>`, e.g. `get-config`.
+pub(crate) fn rpc_operation(raw: &str) -> Option<String> {
+    let mut reader = Reader::from_str(raw);
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) if depth == 1 => {
+                return Some(String::from_utf8_lossy(e.local_name()).into_owned())
+            }
+            Ok(Event::Empty(ref e)) if depth == 1 => {
+                return Some(String::from_utf8_lossy(e.local_name()).into_owned())
+            }
+            Ok(Event::Start(_)) => depth += 1,
+            Ok(Event::End(_)) | Ok(Event::Eof) | Err(_) => return None,
+            _ => (),
+        }
+        buf.clear();
+    }
+}
+
+fn root_element(raw: &str) -> Option<(String, Option<u32>)> {
+    let mut reader = Reader::from_str(raw);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                let name = String::from_utf8_lossy(e.local_name()).into_owned();
+                let message_id = e
+                    .attributes()
+                    .filter_map(|attr| attr.ok())
+                    .find(|attr| attr.key == b"message-id")
+                    .and_then(|attr| String::from_utf8(attr.value.into_owned()).ok())
+                    .and_then(|value| value.parse().ok());
+                return Some((name, message_id));
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => (),
+        }
+        buf.clear();
+    }
+}
+
+fn check_response_message_id<T: RpcRsp>(rsp: &T, id: u32) -> Result<(), NetconfClientError> {
+    let message_id = rsp.get_message_id().expect("no message id");
+    if id != message_id {
+        return Err(NetconfResponseIdError {
+            err: format!("request id: {} response id: {}", id, message_id),
+        });
+    }
+    Ok(())
+}
+
+fn make_return<T: RpcRsp>(rsp: T) -> Result<T, NetconfClientError> {
+    if rsp.is_ok() {
+        Ok(rsp)
+    } else {
+        Err(NetconfError {
+            err: rsp.get_error().unwrap().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[test]
+    fn get_data_content() {
+        let xml = r#"<rpc-reply message-id="101"><data><top/></data></rpc-reply>"#;
+        assert_eq!(data_content(xml), Some("<top/>"));
+        assert_eq!(
+            data_content(r#"<rpc-reply message-id="101"><data/></rpc-reply>"#),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_inline_config() {
+        let cmd = validate(
+            101,
+            ValidateSourceType::Config {
+                config: Data {
+                    xmlns_xc: None,
+                    data: r#"<users xmlns="ns:yang:test"><name>Alice</name></users>"#.to_string(),
+                },
+            },
+        );
+        assert_eq!(
+            cmd,
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><validate><source><config><users xmlns="ns:yang:test"><name>Alice</name></users> </config></source></validate></rpc>"#
+        );
+    }
+
+    #[test]
+    fn partial_lock_namespaces() {
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("t".to_string(), "ns:yang:test".to_string());
+        assert_eq!(
+            partial_lock(101, vec!["/t:users".to_string()], &namespaces),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><partial-lock xmlns:t="ns:yang:test" xmlns="urn:ietf:params:xml:ns:netconf:partial-lock:1.0"><select>/t:users</select></partial-lock></rpc>"#
+        );
+    }
+
+    #[test]
+    fn create_subscription_filters() {
+        assert_eq!(
+            create_subscription(
+                101,
+                Some("NETCONF"),
+                Some(NotificationFilter::Subtree(
+                    r#"<netconf-config-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"/>"#.to_string()
+                )),
+                Some("2021-06-01T10:00:00Z"),
+                None,
+            ),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><create-subscription xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0"><stream>NETCONF</stream><filter type="subtree"><netconf-config-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"/> </filter><startTime>2021-06-01T10:00:00Z</startTime></create-subscription></rpc>"#
+        );
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert(
+            "ncn".to_string(),
+            "urn:ietf:params:xml:ns:yang:ietf-netconf-notifications".to_string(),
+        );
+        assert_eq!(
+            create_subscription(
+                102,
+                None,
+                Some(NotificationFilter::XPath {
+                    select: "/ncn:netconf-session-start".to_string(),
+                    namespaces,
+                }),
+                None,
+                Some("2021-06-02T10:00:00Z"),
+            ),
+            r#"<rpc message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><create-subscription xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0"><filter xmlns:ncn="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications" type="xpath" select="/ncn:netconf-session-start"> </filter><stopTime>2021-06-02T10:00:00Z</stopTime></create-subscription></rpc>"#
+        );
+    }
+
+    #[test]
+    fn establish_subscription_filters() {
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("ex".to_string(), "http://example.com/events".to_string());
+        assert_eq!(
+            establish_subscription(
+                101,
+                StreamSubscription {
+                    stream: "NETCONF".to_string(),
+                    filter: Some(NotificationFilter::XPath {
+                        select: "/ex:foo".to_string(),
+                        namespaces,
+                    }),
+                    stop_time: Some("2021-06-02T10:00:00Z".to_string()),
+                    encoding: Some(SubscriptionEncoding::EncodeXml),
+                    dscp: Some(10),
+                    ..Default::default()
+                },
+            ),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><establish-subscription xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications"><stream>NETCONF</stream><stream-xpath-filter xmlns:ex="http://example.com/events">/ex:foo</stream-xpath-filter><stop-time>2021-06-02T10:00:00Z</stop-time><dscp>10</dscp><encoding>encode-xml</encoding></establish-subscription></rpc>"#
+        );
+        assert_eq!(
+            modify_subscription(
+                102,
+                SubscriptionId(52),
+                Some(NotificationFilter::Subtree(
+                    r#"<foo xmlns="http://example.com/events"/>"#.to_string()
+                )),
+                None,
+            ),
+            r#"<rpc message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><modify-subscription xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications"><id>52</id><stream-subtree-filter><foo xmlns="http://example.com/events"/> </stream-subtree-filter></modify-subscription></rpc>"#
+        );
+        assert_eq!(
+            kill_subscription(103, SubscriptionId(52)),
+            r#"<rpc message-id="103" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><kill-subscription xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications"><id>52</id></kill-subscription></rpc>"#
+        );
+    }
+
+    #[test]
+    fn establish_datastore_subscription_triggers() {
+        assert_eq!(
+            establish_datastore_subscription(
+                101,
+                DatastoreSubscription {
+                    datastore: "operational".to_string(),
+                    filter: Some(NotificationFilter::Subtree(
+                        r#"<interfaces xmlns="http://example.com/interfaces"/>"#.to_string()
+                    )),
+                    trigger: UpdateTrigger::Periodic {
+                        period: 500,
+                        anchor_time: Some("2021-06-01T10:00:00Z".to_string()),
+                    },
+                    stop_time: None,
+                    encoding: None,
+                    dscp: None,
+                },
+            ),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><establish-subscription xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications" xmlns:yp="urn:ietf:params:xml:ns:yang:ietf-yang-push"><yp:datastore xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">ds:operational</yp:datastore><yp:datastore-subtree-filter><interfaces xmlns="http://example.com/interfaces"/> </yp:datastore-subtree-filter><yp:periodic><yp:period>500</yp:period><yp:anchor-time>2021-06-01T10:00:00Z</yp:anchor-time></yp:periodic></establish-subscription></rpc>"#
+        );
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert(
+            "if".to_string(),
+            "http://example.com/interfaces".to_string(),
+        );
+        assert_eq!(
+            establish_datastore_subscription(
+                102,
+                DatastoreSubscription {
+                    datastore: "operational".to_string(),
+                    filter: Some(NotificationFilter::XPath {
+                        select: "/if:interfaces".to_string(),
+                        namespaces,
+                    }),
+                    trigger: UpdateTrigger::OnChange {
+                        dampening_period: Some(100),
+                        sync_on_start: Some(true),
+                    },
+                    stop_time: None,
+                    encoding: Some(SubscriptionEncoding::EncodeXml),
+                    dscp: None,
+                },
+            ),
+            r#"<rpc message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><establish-subscription xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications" xmlns:yp="urn:ietf:params:xml:ns:yang:ietf-yang-push"><yp:datastore xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">ds:operational</yp:datastore><yp:datastore-xpath-filter xmlns:if="http://example.com/interfaces">/if:interfaces</yp:datastore-xpath-filter><yp:on-change><yp:dampening-period>100</yp:dampening-period><yp:sync-on-start>true</yp:sync-on-start></yp:on-change><encoding>encode-xml</encoding></establish-subscription></rpc>"#
+        );
+    }
+
+    #[test]
+    fn nmda_operations() {
+        assert_eq!(
+            nmda_get_data(
+                101,
+                Datastore::operational(),
+                GetDataOptions {
+                    filter: Some(Filter::subtree(
+                        r#"<interfaces xmlns="http://example.com/interfaces"/>"#
+                    )),
+                    config_filter: Some(false),
+                    origin_filter: Some(OriginFilter::Exclude(vec![Origin::system()])),
+                    max_depth: Some(3),
+                    with_origin: true,
+                    with_defaults: Some(WithDefaultsMode::ReportAll),
+                },
+            ),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><get-data xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-nmda"><datastore xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">ds:operational</datastore><subtree-filter><interfaces xmlns="http://example.com/interfaces"/> </subtree-filter><config-filter>false</config-filter><negated-origin-filter xmlns:or="urn:ietf:params:xml:ns:yang:ietf-origin">or:system</negated-origin-filter><max-depth>3</max-depth><with-origin/><with-defaults xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-with-defaults">report-all</with-defaults></get-data></rpc>"#
+        );
+        assert_eq!(
+            nmda_get_data(
+                104,
+                Datastore::running(),
+                GetDataOptions {
+                    filter: Some(Filter::xpath(
+                        "/t:users",
+                        BTreeMap::from([("t".to_string(), "ns:yang:test".to_string())]),
+                    )),
+                    ..Default::default()
+                },
+            ),
+            r#"<rpc message-id="104" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><get-data xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-nmda"><datastore xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">ds:running</datastore><xpath-filter xmlns:t="ns:yang:test">/t:users</xpath-filter></get-data></rpc>"#
+        );
+        assert_eq!(
+            nmda_get_data(
+                102,
+                Datastore::new("http://example.com/datastores", "ephemeral"),
+                GetDataOptions::default(),
+            ),
+            r#"<rpc message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><get-data xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-nmda"><datastore xmlns:ds="http://example.com/datastores">ds:ephemeral</datastore></get-data></rpc>"#
+        );
+        assert_eq!(
+            nmda_edit_data(
+                103,
+                Datastore::from(DatastoreType::Candidate),
+                r#"<system xmlns="http://example.com/system"><hostname>r1</hostname></system>"#
+                    .to_string(),
+                Some(DefaultOperationType::Replace),
+            ),
+            r#"<rpc message-id="103" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><edit-data xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-nmda"><datastore xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">ds:candidate</datastore><default-operation>replace</default-operation><config xmlns:xc="urn:ietf:params:xml:ns:netconf:base:1.0"><system xmlns="http://example.com/system"><hostname>r1</hostname></system> </config></edit-data></rpc>"#
+        );
+    }
+
+    #[test]
+    fn typed_rpc() {
+        #[derive(Serialize)]
+        struct Value {
+            #[serde(rename = "$value")]
+            value: String,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "reset-counters")]
+        struct ResetCounters {
+            interface: Vec<Value>,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "restart")]
+        struct Restart {}
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct ResetCountersOutput {
+            #[serde(rename = "reset-time")]
+            reset_time: String,
+            cleared: u32,
+        }
+
+        let input = ResetCounters {
+            interface: vec![
+                Value {
+                    value: "eth0".to_string(),
+                },
+                Value {
+                    value: "eth1".to_string(),
+                },
+            ],
+        };
+        let data = rpc_input("urn:example:interfaces?a&b", &input).unwrap();
+        assert_eq!(
+            raw_rpc(101, &data),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><reset-counters xmlns="urn:example:interfaces?a&amp;b"><interface>eth0</interface><interface>eth1</interface></reset-counters> </rpc>"#
+        );
+
+        let xml = r#"<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><reset-time xmlns="urn:example:interfaces">2024-01-01T00:00:00Z</reset-time><cleared xmlns="urn:example:interfaces">2</cleared></rpc-reply>"#;
+        assert_eq!(
+            parse_rpc_reply::<ResetCountersOutput>(xml, 101).unwrap(),
+            ResetCountersOutput {
+                reset_time: "2024-01-01T00:00:00Z".to_string(),
+                cleared: 2,
+            }
+        );
+        let xml = r#"<rpc-reply message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><rpc-error><error-type>application</error-type><error-tag>operation-failed</error-tag><error-severity>error</error-severity></rpc-error></rpc-reply>"#;
+        match parse_rpc_reply::<ResetCountersOutput>(xml, 102) {
+            Err(NetconfError { err }) => assert_eq!(err.len(), 1),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert_eq!(
+            rpc_input("urn:example:system", &Restart {}).unwrap(),
+            r#"<restart xmlns="urn:example:system"/>"#
+        );
+    }
+
+    #[test]
+    fn yang_action() {
+        #[derive(Serialize)]
+        struct Value {
+            #[serde(rename = "$value")]
+            value: String,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "reset")]
+        struct Reset {
+            #[serde(rename = "reset-at")]
+            reset_at: Value,
+        }
+
+        let path = ActionPath::new("urn:example:server-farm", "server")
+            .key("name", "apache-1")
+            .node("rack")
+            .key("row", "1")
+            .key("id", "a&b");
+        let input = Reset {
+            reset_at: Value {
+                value: "2014-07-29T13:42:00Z".to_string(),
+            },
+        };
+        assert_eq!(
+            raw_rpc(101, &action_input(&path, &input).unwrap()),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><action xmlns="urn:ietf:params:xml:ns:yang:1"><server xmlns="urn:example:server-farm"><name>apache-1</name><rack><row>1</row><id>a&amp;b</id><reset xmlns="urn:example:server-farm"><reset-at>2014-07-29T13:42:00Z</reset-at></reset></rack></server></action> </rpc>"#
+        );
+
+        #[derive(Serialize)]
+        #[serde(rename = "clear")]
+        struct Clear {}
+
+        let path = ActionPath::new("urn:example:interfaces", "interfaces")
+            .node("interface")
+            .key("name", "eth0")
+            .augmented_node("urn:example:counters", "counters");
+        assert_eq!(path.namespace(), "urn:example:counters");
+        assert_eq!(
+            action_input(&path, &Clear {}).unwrap(),
+            r#"<action xmlns="urn:ietf:params:xml:ns:yang:1"><interfaces xmlns="urn:example:interfaces"><interface><name>eth0</name><counters xmlns="urn:example:counters"><clear xmlns="urn:example:counters"/></counters></interface></interfaces></action>"#
+        );
+    }
+
+    #[test]
+    fn url_operations() {
+        assert_eq!(
+            edit_config_url(
+                101,
+                DatastoreType::Candidate,
+                "file:///config.xml",
+                Some(DefaultOperationType::Replace),
+                None,
+                None,
+            ),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><edit-config><target><candidate/></target><default-operation>replace</default-operation><url>file:///config.xml</url></edit-config></rpc>"#
+        );
+        assert_eq!(
+            copy_config(
+                102,
+                DatastoreType::Running.into(),
+                CopyConfigSourceType::Url {
+                    url: Url::new("ftp://example.com/config.xml"),
+                },
+                None,
+            ),
+            r#"<rpc message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><copy-config><target><running/></target><source><url>ftp://example.com/config.xml</url></source></copy-config></rpc>"#
+        );
+        assert_eq!(
+            delete_config(
+                103,
+                ConfigTargetType::Url {
+                    url: Url::new("file:///config.xml"),
+                },
+            ),
+            r#"<rpc message-id="103" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><delete-config><target><url>file:///config.xml</url></target></delete-config></rpc>"#
+        );
+    }
+
+    #[test]
+    fn xpath_filter() {
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("t".to_string(), "ns:yang:test".to_string());
+        assert_eq!(
+            get_config(
+                101,
+                DatastoreType::Running,
+                Some(Filter::xpath("/t:users/t:user[t:name='a']", namespaces)),
+                None,
+            ),
+            r#"<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><get-config><source><running/></source><filter xmlns:t="ns:yang:test" type="xpath" select="/t:users/t:user[t:name=&apos;a&apos;]"> </filter></get-config></rpc>"#
+        );
+        assert_eq!(
+            get(
+                102,
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"/>"#)),
+                None
+            ),
+            r#"<rpc message-id="102" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><get><filter type="subtree"><users xmlns="ns:yang:test"/> </filter></get></rpc>"#
+        );
+    }
+
+    #[test]
+    fn report_all_tagged() {
+        let xml = r#"<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" xmlns:wd="urn:ietf:params:xml:ns:netconf:default:1.0"><data><interfaces xmlns="http://example.com/interfaces"><interface><name>eth0</name><mtu wd:default="true">1500</mtu><status>up</status></interface></interfaces></data></rpc-reply>"#;
+        let reply = parse_get_reply(xml, 101, Some(WithDefaultsMode::ReportAllTagged)).unwrap();
+        let nodes = reply.data_nodes.unwrap();
+        let interface = nodes[0].child("interface").unwrap();
+        assert!(interface.child("mtu").unwrap().is_default());
+        assert!(!interface.child("status").unwrap().is_default());
+        assert_eq!(
+            parse_get_reply(xml, 101, Some(WithDefaultsMode::ReportAll))
+                .unwrap()
+                .data_nodes,
+            None
+        );
+    }
+
+    #[test]
+    fn get_schema_text() {
+        let xml = r#"<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><data xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring">
+module test {
+  prefix "t";
+  leaf a { type string; must "count(b) &lt; 3"; }
+}
+</data></rpc-reply>"#;
+        let reply = parse_get_schema_reply(xml, 101).unwrap();
+        assert_eq!(
+            reply.schema,
+            "module test {\n  prefix \"t\";\n  leaf a { type string; must \"count(b) < 3\"; }\n}"
+        );
+        assert_eq!(
+            schema_text("<![CDATA[module test { leaf a { must \"b < 3\"; } }]]>"),
+            "module test { leaf a { must \"b < 3\"; } }"
+        );
+        let yin = r#"<module name="test" xmlns="urn:ietf:params:xml:ns:yang:yin:1"><prefix value="t"/></module>"#;
+        assert_eq!(schema_text(yin), yin);
+    }
+
+    #[test]
+    fn message_id_from_reply() {
+        let xml = r#"
+<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+    <ok/>
+</rpc-reply>
+"#;
+        assert_eq!(reply_message_id(xml), Some(101));
+        assert_eq!(
+            reply_message_id("<hello><session-id>4</session-id></hello>"),
+            None
+        );
+        assert_eq!(
+            rpc_operation(&close_session(102)),
+            Some("close-session".to_string())
+        );
+        assert_eq!(
+            rpc_operation(&get_config(103, DatastoreType::Running, None, None)),
+            Some("get-config".to_string())
+        );
+    }
+}
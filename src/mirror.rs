@@ -0,0 +1,677 @@
+use crate::errors::NetconfClientError;
+use crate::models::requests::{DatastoreSubscription, Filter, NotificationFilter, UpdateTrigger};
+use crate::models::subscribed_notifications::{SubscriptionId, SubscriptionStateChange};
+use crate::models::yang_push::{YangPatchEdit, YangPatchOperation, YangPushUpdate};
+use crate::netconf_client::NetconfClient;
+use crate::notification::Notification;
+use crate::xml::Element;
+use std::collections::BTreeMap;
+use std::io;
+
+/// Change applied to a [`StateMirror`] replica.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MirrorChange<'a> {
+    /// The replica was replaced with the complete state retrieved from the
+    /// server.
+    Resync,
+    Edit(&'a YangPatchEdit),
+}
+
+type ChangeCallback = Box<dyn FnMut(&MirrorChange, &[Element]) + Send>;
+
+/// In-memory replica of an operational state subtree. The subtree is
+/// retrieved with `<get>` and then kept up to date with the YANG Patch edits
+/// of an on-change YANG-Push subscription on the same session.
+///
+/// The replica is retrieved again when the subscription is suspended or
+/// terminated, when an update is incomplete and, after reconnecting, when
+/// the session drops. Without the YANG schema, list entries are identified
+/// by their first child, which holds the key.
+pub struct StateMirror {
+    filter: String,
+    dampening_period: Option<u32>,
+    subscription: Option<SubscriptionId>,
+    root: Element,
+    stale: bool,
+    callbacks: Vec<ChangeCallback>,
+}
+
+impl StateMirror {
+    /// `filter` is a subtree filter selecting the mirrored nodes, e.g.
+    /// `<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces"/>`.
+    pub fn new<S: Into<String>>(filter: S) -> StateMirror {
+        StateMirror {
+            filter: filter.into(),
+            dampening_period: None,
+            subscription: None,
+            root: data_root(),
+            stale: true,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Dampening period of the subscription in centiseconds.
+    pub fn with_dampening_period(mut self, dampening_period: u32) -> StateMirror {
+        self.dampening_period = Some(dampening_period);
+        self
+    }
+
+    /// Registers a callback invoked with every change and the updated
+    /// replica.
+    pub fn on_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(&MirrorChange, &[Element]) + Send + 'static,
+    {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Top level nodes of the replica.
+    pub fn data(&self) -> &[Element] {
+        &self.root.children
+    }
+
+    pub fn subscription(&self) -> Option<SubscriptionId> {
+        self.subscription
+    }
+
+    /// Whether the replica may differ from the server state until the next
+    /// resync.
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Replaces the subscription and retrieves the complete subtree. The
+    /// subscription is established first so that no change is missed.
+    pub fn resync(&mut self, client: &mut NetconfClient) -> Result<(), NetconfClientError> {
+        self.stale = true;
+        if let Some(id) = self.subscription.take() {
+            if client.dynamic_subscriptions().contains(&id) {
+                client.delete_subscription(id)?;
+            }
+        }
+        let rsp = client.establish_datastore_subscription(DatastoreSubscription {
+            datastore: "operational".to_string(),
+            filter: Some(NotificationFilter::Subtree(self.filter.clone())),
+            trigger: UpdateTrigger::OnChange {
+                dampening_period: self.dampening_period,
+                sync_on_start: Some(false),
+            },
+            stop_time: None,
+            encoding: None,
+            dscp: None,
+        })?;
+        self.subscription = rsp.id;
+        let rsp = client.get(Some(Filter::subtree(&self.filter)), None)?;
+        self.load(rsp.data.as_deref().unwrap_or(""))
+    }
+
+    /// Waits for the next notification and applies it, resyncing the replica
+    /// when needed. A session which dropped is connected again. Returns
+    /// without changes when no notification arrived before the SSH timeout.
+    pub fn update(&mut self, client: &mut NetconfClient) -> Result<(), NetconfClientError> {
+        if self.stale {
+            return self.resync(client);
+        }
+        match client.next_notification() {
+            Ok(Some(notification)) => self.apply(&notification)?,
+            Ok(None) => self.stale = true,
+            Err(NetconfClientError::IOError(err)) if err.kind() == io::ErrorKind::TimedOut => {
+                return Ok(())
+            }
+            Err(NetconfClientError::IOError(_))
+            | Err(NetconfClientError::SSH2Error(_))
+            | Err(NetconfClientError::SSHClientError { .. }) => {
+                // Stale before reconnecting, the replica is not updated
+                // anymore if the reconnect fails.
+                self.subscription = None;
+                self.stale = true;
+                client.connect()?;
+            }
+            Err(err) => return Err(err),
+        }
+        if self.stale {
+            self.resync(client)?;
+        }
+        Ok(())
+    }
+
+    /// Applies a notification received on the mirror's session. Updates of
+    /// other subscriptions are ignored. If the update cannot be applied, the
+    /// replica is marked stale and the error is returned.
+    pub fn apply(&mut self, notification: &Notification) -> Result<(), NetconfClientError> {
+        let subscription = match self.subscription {
+            Some(subscription) => subscription,
+            None => return Ok(()),
+        };
+        if let Some(change) = SubscriptionStateChange::from_notification(notification)? {
+            if change.id() == subscription {
+                match change {
+                    SubscriptionStateChange::Suspended(_)
+                    | SubscriptionStateChange::Terminated(_)
+                    | SubscriptionStateChange::Completed(_) => self.stale = true,
+                    _ => (),
+                }
+            }
+            return Ok(());
+        }
+        let update = match YangPushUpdate::from_notification(notification) {
+            Ok(Some(update)) if update.id() == subscription => update,
+            Ok(_) => return Ok(()),
+            Err(err) => {
+                self.stale = true;
+                return Err(err);
+            }
+        };
+        match update {
+            YangPushUpdate::Update(update) => {
+                self.root.children = update.datastore_contents;
+                self.stale = update.incomplete_update;
+                self.notify(&MirrorChange::Resync);
+            }
+            YangPushUpdate::ChangeUpdate(update) => {
+                for edit in &update.edits {
+                    if let Err(err) = apply_edit(&mut self.root, edit) {
+                        self.stale = true;
+                        return Err(err);
+                    }
+                    self.notify(&MirrorChange::Edit(edit));
+                }
+                if update.incomplete_update {
+                    self.stale = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load(&mut self, data: &str) -> Result<(), NetconfClientError> {
+        let root = Element::parse(&format!("<data>{}</data>", data))?;
+        self.root.children = root.children.iter().map(Element::detached).collect();
+        self.stale = false;
+        self.notify(&MirrorChange::Resync);
+        Ok(())
+    }
+
+    fn notify(&mut self, change: &MirrorChange) {
+        for callback in &mut self.callbacks {
+            callback(change, &self.root.children);
+        }
+    }
+}
+
+fn data_root() -> Element {
+    Element {
+        name: "data".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Step of a YANG Patch target, either XPath like
+/// `/if:interfaces/if:interface[if:name='eth0']` or RESTCONF like
+/// `/ietf-interfaces:interfaces/interface=eth0`.
+#[derive(Debug, PartialEq)]
+struct Step {
+    prefix: Option<String>,
+    name: String,
+    /// Key values, named in XPath predicates and positional in RESTCONF.
+    keys: Vec<(Option<String>, String)>,
+}
+
+fn parse_target(target: &str) -> Result<Vec<Step>, NetconfClientError> {
+    split_outside_quotes(target.trim_start_matches('/'), '/')
+        .into_iter()
+        .filter(|step| !step.is_empty())
+        .map(parse_step)
+        .collect()
+}
+
+fn parse_step(step: &str) -> Result<Step, NetconfClientError> {
+    let end = step.find(['[', '=']).unwrap_or(step.len());
+    let (prefix, name) = split_qname(&step[..end]);
+    let mut keys = Vec::new();
+    let rest = &step[end..];
+    if let Some(values) = rest.strip_prefix('=') {
+        for value in values.split(',') {
+            keys.push((None, percent_decode(value)));
+        }
+    } else {
+        for predicate in split_outside_quotes(rest, ']') {
+            let predicate = predicate.trim();
+            if predicate.is_empty() {
+                continue;
+            }
+            let (key, value) = predicate
+                .strip_prefix('[')
+                .and_then(|predicate| predicate.split_once('='))
+                .ok_or_else(|| invalid(&format!("unsupported predicate in {}", step)))?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+                .or_else(|| {
+                    value
+                        .strip_prefix('"')
+                        .and_then(|value| value.strip_suffix('"'))
+                })
+                .unwrap_or(value);
+            keys.push((Some(split_qname(key.trim()).1), value.to_string()));
+        }
+    }
+    Ok(Step { prefix, name, keys })
+}
+
+fn split_qname(qname: &str) -> (Option<String>, String) {
+    match qname.split_once(':') {
+        Some((prefix, name)) => (Some(prefix.to_string()), name.to_string()),
+        None => (None, qname.to_string()),
+    }
+}
+
+fn split_outside_quotes(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == separator => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => (),
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = if bytes[i] == b'%' {
+            text.get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Prefixes which are not declared XML prefixes are module names, which
+/// cannot be resolved without the YANG library, so only the name is matched.
+fn matches_step(element: &Element, step: &Step, namespaces: &BTreeMap<String, String>) -> bool {
+    if element.name != step.name {
+        return false;
+    }
+    let namespace = step
+        .prefix
+        .as_ref()
+        .and_then(|prefix| namespaces.get(prefix));
+    if namespace.is_some() && element.namespace.as_ref() != namespace {
+        return false;
+    }
+    step.keys
+        .iter()
+        .enumerate()
+        .all(|(i, (key, value))| match key {
+            Some(key) => element.child_text(key) == Some(value.as_str()),
+            None => element.children.get(i).map(|child| child.text.as_str()) == Some(value),
+        })
+}
+
+/// Finds the parent of the target node, creating missing ancestors when
+/// `create` is set.
+fn find_parent<'a>(
+    root: &'a mut Element,
+    steps: &[Step],
+    namespaces: &BTreeMap<String, String>,
+    create: bool,
+) -> Option<&'a mut Element> {
+    let mut element = root;
+    for step in steps {
+        let index = match element
+            .children
+            .iter()
+            .position(|child| matches_step(child, step, namespaces))
+        {
+            Some(index) => index,
+            None if create => {
+                element.children.push(new_node(element, step, namespaces));
+                element.children.len() - 1
+            }
+            None => return None,
+        };
+        element = &mut element.children[index];
+    }
+    Some(element)
+}
+
+fn new_node(parent: &Element, step: &Step, namespaces: &BTreeMap<String, String>) -> Element {
+    let namespace = step
+        .prefix
+        .as_ref()
+        .and_then(|prefix| namespaces.get(prefix))
+        .or(parent.namespace.as_ref())
+        .cloned();
+    let mut declarations = BTreeMap::new();
+    if let Some(namespace) = &namespace {
+        if parent.namespace.as_ref() != Some(namespace) {
+            declarations.insert(String::new(), namespace.clone());
+        }
+    }
+    let children = step
+        .keys
+        .iter()
+        .filter_map(|(key, value)| {
+            key.as_ref().map(|key| Element {
+                name: key.clone(),
+                namespace: namespace.clone(),
+                text: value.clone(),
+                ..Default::default()
+            })
+        })
+        .collect();
+    Element {
+        name: step.name.clone(),
+        namespace,
+        declarations,
+        children,
+        ..Default::default()
+    }
+}
+
+fn apply_edit(root: &mut Element, edit: &YangPatchEdit) -> Result<(), NetconfClientError> {
+    let steps = parse_target(&edit.target)?;
+    let (target, ancestors) = steps
+        .split_last()
+        .ok_or_else(|| invalid("empty yang-patch target"))?;
+    let namespaces = &edit.namespaces;
+    let create = !matches!(
+        edit.operation,
+        YangPatchOperation::Delete | YangPatchOperation::Remove
+    );
+    let parent = match find_parent(root, ancestors, namespaces, create) {
+        Some(parent) => parent,
+        None => return Ok(()),
+    };
+    let is_target = |child: &Element| matches_step(child, target, namespaces);
+    match edit.operation {
+        YangPatchOperation::Delete | YangPatchOperation::Remove => {
+            parent.children.retain(|child| !is_target(child));
+        }
+        YangPatchOperation::Create | YangPatchOperation::Replace => {
+            let index = parent
+                .children
+                .iter()
+                .position(is_target)
+                .unwrap_or(parent.children.len());
+            parent.children.retain(|child| !is_target(child));
+            let index = index.min(parent.children.len());
+            parent
+                .children
+                .splice(index..index, edit.value.iter().cloned());
+        }
+        YangPatchOperation::Merge => {
+            for value in &edit.value {
+                match parent.children.iter_mut().find(|child| is_target(child)) {
+                    Some(existing) => merge(existing, value),
+                    None => parent.children.push(value.clone()),
+                }
+            }
+        }
+        YangPatchOperation::Insert | YangPatchOperation::Move => {
+            let moved: Vec<Element> = parent
+                .children
+                .iter()
+                .filter(|child| is_target(child))
+                .cloned()
+                .collect();
+            parent.children.retain(|child| !is_target(child));
+            let nodes = match edit.operation {
+                YangPatchOperation::Move => moved,
+                _ => edit.value.clone(),
+            };
+            let index = insert_index(parent, edit)?;
+            parent.children.splice(index..index, nodes);
+        }
+    }
+    Ok(())
+}
+
+fn insert_index(parent: &Element, edit: &YangPatchEdit) -> Result<usize, NetconfClientError> {
+    let point = match &edit.point {
+        Some(point) => parse_target(point)?.pop(),
+        None => None,
+    };
+    let position = |point: &Step| {
+        parent
+            .children
+            .iter()
+            .position(|child| matches_step(child, point, &edit.namespaces))
+    };
+    let index = match (edit.r#where.as_deref(), point.as_ref().and_then(position)) {
+        (Some("first"), _) => 0,
+        (Some("before"), Some(index)) => index,
+        (Some("after"), Some(index)) => index + 1,
+        _ => parent.children.len(),
+    };
+    Ok(index)
+}
+
+/// Leaves are replaced, other nodes are merged recursively. Sibling nodes
+/// with children are the same node when their first children match.
+fn merge(target: &mut Element, value: &Element) {
+    for attribute in &value.attributes {
+        target.attributes.retain(|existing| {
+            existing.name != attribute.name || existing.namespace != attribute.namespace
+        });
+        target.attributes.push(attribute.clone());
+    }
+    if value.children.is_empty() {
+        target.text = value.text.clone();
+        return;
+    }
+    for child in &value.children {
+        match target
+            .children
+            .iter_mut()
+            .find(|existing| same_node(existing, child))
+        {
+            Some(existing) => merge(existing, child),
+            None => target.children.push(child.clone()),
+        }
+    }
+}
+
+fn same_node(a: &Element, b: &Element) -> bool {
+    if a.name != b.name || a.namespace != b.namespace {
+        return false;
+    }
+    match (a.children.first(), b.children.first()) {
+        (Some(a), Some(b)) if a.children.is_empty() && b.children.is_empty() => {
+            a.name != b.name || a.text == b.text
+        }
+        _ => true,
+    }
+}
+
+fn invalid(message: &str) -> NetconfClientError {
+    NetconfClientError::DeserializeError(quick_xml::DeError::Custom(message.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn change_update(edits: &str) -> Notification {
+        Notification::parse(&format!(
+            r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0" xmlns:if="http://example.com/interfaces">
+    <eventTime>2021-06-01T10:00:00Z</eventTime>
+    <push-change-update xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-push">
+        <id>7</id>
+        <datastore-changes><yang-patch><patch-id>0</patch-id>{}</yang-patch></datastore-changes>
+    </push-change-update>
+</notification>"#,
+            edits
+        ))
+    }
+
+    fn mirror() -> StateMirror {
+        let mut mirror = StateMirror::new(r#"<interfaces xmlns="http://example.com/interfaces"/>"#);
+        mirror.subscription = Some(SubscriptionId(7));
+        mirror
+            .load(
+                r#"<interfaces xmlns="http://example.com/interfaces">
+    <interface><name>eth0</name><oper-status>up</oper-status></interface>
+    <interface><name>eth1</name><oper-status>up</oper-status></interface>
+</interfaces>"#,
+            )
+            .unwrap();
+        mirror
+    }
+
+    fn xml(mirror: &StateMirror) -> String {
+        mirror.data().iter().map(Element::to_xml).collect()
+    }
+
+    #[test]
+    fn parse_targets() {
+        assert_eq!(
+            parse_target("/if:interfaces/if:interface[if:name='eth/0'][unit=\"1\"]").unwrap(),
+            vec![
+                Step {
+                    prefix: Some("if".to_string()),
+                    name: "interfaces".to_string(),
+                    keys: vec![],
+                },
+                Step {
+                    prefix: Some("if".to_string()),
+                    name: "interface".to_string(),
+                    keys: vec![
+                        (Some("name".to_string()), "eth/0".to_string()),
+                        (Some("unit".to_string()), "1".to_string()),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            parse_target("/ietf-interfaces:interfaces/interface=eth%2F0,1").unwrap()[1],
+            Step {
+                prefix: None,
+                name: "interface".to_string(),
+                keys: vec![(None, "eth/0".to_string()), (None, "1".to_string())],
+            }
+        );
+    }
+
+    #[test]
+    fn apply_edits() {
+        let mut mirror = mirror();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let received = changes.clone();
+        mirror.on_change(move |change, _| {
+            if let MirrorChange::Edit(edit) = change {
+                received.lock().unwrap().push(edit.edit_id.clone());
+            }
+        });
+
+        mirror
+            .apply(&change_update(
+                r#"<edit>
+    <edit-id>1</edit-id>
+    <operation>replace</operation>
+    <target>/if:interfaces/if:interface[if:name='eth0']/if:oper-status</target>
+    <value><if:oper-status>down</if:oper-status></value>
+</edit>
+<edit>
+    <edit-id>2</edit-id>
+    <operation>delete</operation>
+    <target>/ietf-interfaces:interfaces/interface=eth1</target>
+</edit>
+<edit>
+    <edit-id>3</edit-id>
+    <operation>merge</operation>
+    <target>/if:interfaces</target>
+    <value><interfaces xmlns="http://example.com/interfaces"><interface><name>eth2</name><oper-status>up</oper-status></interface></interfaces></value>
+</edit>
+<edit>
+    <edit-id>4</edit-id>
+    <operation>create</operation>
+    <target>/if:interfaces/if:interface[if:name='eth3']/if:mtu</target>
+    <value><if:mtu>1500</if:mtu></value>
+</edit>"#,
+            ))
+            .unwrap();
+        assert!(!mirror.is_stale());
+        assert_eq!(*changes.lock().unwrap(), vec!["1", "2", "3", "4"]);
+        assert_eq!(
+            xml(&mirror),
+            concat!(
+                r#"<interfaces xmlns="http://example.com/interfaces">"#,
+                r#"<interface><name>eth0</name><if:oper-status xmlns:if="http://example.com/interfaces">down</if:oper-status></interface>"#,
+                r#"<interface><name>eth2</name><oper-status>up</oper-status></interface>"#,
+                r#"<interface><name>eth3</name><if:mtu xmlns:if="http://example.com/interfaces">1500</if:mtu></interface>"#,
+                r#"</interfaces>"#
+            )
+        );
+
+        mirror
+            .apply(&change_update(
+                r#"<edit>
+    <edit-id>5</edit-id>
+    <operation>move</operation>
+    <target>/if:interfaces/if:interface[if:name='eth3']</target>
+    <point>/if:interfaces/if:interface[if:name='eth0']</point>
+    <where>before</where>
+</edit>"#,
+            ))
+            .unwrap();
+        let names: Vec<_> = mirror.data()[0]
+            .children
+            .iter()
+            .filter_map(|interface| interface.child_text("name"))
+            .collect();
+        assert_eq!(names, vec!["eth3", "eth0", "eth2"]);
+    }
+
+    #[test]
+    fn stale_replica() {
+        let mut mirror = mirror();
+        let other = change_update(
+            r#"<edit><edit-id>1</edit-id><operation>delete</operation><target>/if:interfaces</target></edit>"#,
+        );
+        mirror.subscription = Some(SubscriptionId(8));
+        mirror.apply(&other).unwrap();
+        assert_eq!(mirror.data().len(), 1);
+
+        mirror.subscription = Some(SubscriptionId(7));
+        let invalid = change_update(
+            r#"<edit><edit-id>1</edit-id><operation>unknown</operation><target>/if:interfaces</target></edit>"#,
+        );
+        assert!(mirror.apply(&invalid).is_err());
+        assert!(mirror.is_stale());
+
+        let mut mirror = self::mirror();
+        let suspended = Notification::parse(
+            r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0"><eventTime>2021-06-01T10:00:00Z</eventTime><subscription-suspended xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications"><id>7</id><reason>sn:insufficient-resources</reason></subscription-suspended></notification>"#,
+        );
+        mirror.apply(&suspended).unwrap();
+        assert!(mirror.is_stale());
+    }
+}
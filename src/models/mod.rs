@@ -1,2 +1,7 @@
+pub mod netconf_monitoring;
+pub mod netconf_notifications;
 pub mod replies;
 pub mod requests;
+pub mod subscribed_notifications;
+pub mod yang_library;
+pub mod yang_push;
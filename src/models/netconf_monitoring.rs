@@ -0,0 +1,212 @@
+use serde::Deserialize;
+
+/// RFC 6022 `netconf-state` container. Containers not selected by the
+/// request filter are `None`.
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct NetconfState {
+    pub capabilities: Option<StateCapabilities>,
+    pub datastores: Option<Datastores>,
+    pub schemas: Option<Schemas>,
+    pub sessions: Option<Sessions>,
+    pub statistics: Option<Statistics>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct StateCapabilities {
+    #[serde(rename = "capability", default)]
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct Datastores {
+    #[serde(rename = "datastore", default)]
+    pub datastores: Vec<DatastoreState>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct DatastoreState {
+    pub name: String,
+    pub locks: Option<Locks>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct Locks {
+    #[serde(rename = "global-lock")]
+    pub global_lock: Option<GlobalLock>,
+    #[serde(rename = "partial-lock", default)]
+    pub partial_locks: Vec<PartialLockState>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct GlobalLock {
+    #[serde(rename = "locked-by-session")]
+    pub locked_by_session: u32,
+    #[serde(rename = "locked-time")]
+    pub locked_time: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct PartialLockState {
+    #[serde(rename = "lock-id")]
+    pub lock_id: u32,
+    #[serde(rename = "locked-by-session")]
+    pub locked_by_session: u32,
+    #[serde(rename = "locked-time")]
+    pub locked_time: String,
+    #[serde(rename = "select", default)]
+    pub select: Vec<String>,
+    #[serde(rename = "locked-node", default)]
+    pub locked_nodes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct Schemas {
+    #[serde(rename = "schema", default)]
+    pub schemas: Vec<SchemaState>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SchemaState {
+    pub identifier: String,
+    pub version: String,
+    pub format: String,
+    pub namespace: String,
+    #[serde(rename = "location", default)]
+    pub locations: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct Sessions {
+    #[serde(rename = "session", default)]
+    pub sessions: Vec<SessionState>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SessionState {
+    #[serde(rename = "session-id")]
+    pub session_id: u32,
+    pub transport: String,
+    pub username: String,
+    #[serde(rename = "source-host")]
+    pub source_host: Option<String>,
+    #[serde(rename = "login-time")]
+    pub login_time: String,
+    #[serde(rename = "in-rpcs", default)]
+    pub in_rpcs: u32,
+    #[serde(rename = "in-bad-rpcs", default)]
+    pub in_bad_rpcs: u32,
+    #[serde(rename = "out-rpc-errors", default)]
+    pub out_rpc_errors: u32,
+    #[serde(rename = "out-notifications", default)]
+    pub out_notifications: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct Statistics {
+    #[serde(rename = "netconf-start-time")]
+    pub netconf_start_time: Option<String>,
+    #[serde(rename = "in-bad-hellos", default)]
+    pub in_bad_hellos: u32,
+    #[serde(rename = "in-sessions", default)]
+    pub in_sessions: u32,
+    #[serde(rename = "dropped-sessions", default)]
+    pub dropped_sessions: u32,
+    #[serde(rename = "in-rpcs", default)]
+    pub in_rpcs: u32,
+    #[serde(rename = "in-bad-rpcs", default)]
+    pub in_bad_rpcs: u32,
+    #[serde(rename = "out-rpc-errors", default)]
+    pub out_rpc_errors: u32,
+    #[serde(rename = "out-notifications", default)]
+    pub out_notifications: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn netconf_state() {
+        let xml = r#"
+<netconf-state xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring">
+    <datastores>
+        <datastore>
+            <name>running</name>
+            <locks>
+                <global-lock>
+                    <locked-by-session>4</locked-by-session>
+                    <locked-time>2021-06-01T10:00:00Z</locked-time>
+                </global-lock>
+            </locks>
+        </datastore>
+        <datastore>
+            <name>candidate</name>
+            <locks>
+                <partial-lock>
+                    <lock-id>127</lock-id>
+                    <locked-by-session>5</locked-by-session>
+                    <locked-time>2021-06-01T10:00:00Z</locked-time>
+                    <select>/t:users</select>
+                    <locked-node>/t:users</locked-node>
+                </partial-lock>
+            </locks>
+        </datastore>
+        <datastore>
+            <name>startup</name>
+        </datastore>
+    </datastores>
+    <schemas>
+        <schema>
+            <identifier>ietf-netconf</identifier>
+            <version>2011-06-01</version>
+            <format>yang</format>
+            <namespace>urn:ietf:params:xml:ns:netconf:base:1.0</namespace>
+            <location>NETCONF</location>
+        </schema>
+    </schemas>
+    <sessions>
+        <session>
+            <session-id>4</session-id>
+            <transport xmlns:ncm="urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring">ncm:netconf-ssh</transport>
+            <username>root</username>
+            <source-host>172.17.0.1</source-host>
+            <login-time>2021-06-01T10:00:00Z</login-time>
+            <in-rpcs>12</in-rpcs>
+            <in-bad-rpcs>1</in-bad-rpcs>
+            <out-rpc-errors>1</out-rpc-errors>
+            <out-notifications>0</out-notifications>
+        </session>
+    </sessions>
+    <statistics>
+        <netconf-start-time>2021-06-01T09:00:00Z</netconf-start-time>
+        <in-bad-hellos>0</in-bad-hellos>
+        <in-sessions>7</in-sessions>
+        <dropped-sessions>2</dropped-sessions>
+        <in-rpcs>40</in-rpcs>
+        <in-bad-rpcs>1</in-bad-rpcs>
+        <out-rpc-errors>3</out-rpc-errors>
+        <out-notifications>5</out-notifications>
+    </statistics>
+</netconf-state>
+"#;
+        let state: NetconfState = from_str(xml).unwrap();
+        let datastores = state.datastores.unwrap().datastores;
+        assert_eq!(datastores.len(), 3);
+        let locks = datastores[0].locks.as_ref().unwrap();
+        assert_eq!(locks.global_lock.as_ref().unwrap().locked_by_session, 4);
+        let locks = datastores[1].locks.as_ref().unwrap();
+        assert_eq!(locks.partial_locks[0].lock_id, 127);
+        assert_eq!(locks.partial_locks[0].locked_nodes, vec!["/t:users"]);
+        assert!(datastores[2].locks.is_none());
+        assert_eq!(state.schemas.unwrap().schemas[0].identifier, "ietf-netconf");
+        let session = &state.sessions.unwrap().sessions[0];
+        assert_eq!(session.transport, "ncm:netconf-ssh");
+        assert_eq!(session.source_host, Some("172.17.0.1".to_string()));
+        assert_eq!(session.in_rpcs, 12);
+        let statistics = state.statistics.unwrap();
+        assert_eq!(statistics.in_sessions, 7);
+        assert_eq!(statistics.out_notifications, 5);
+        assert!(state.capabilities.is_none());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::errors::NetconfClientError;
+use crate::notification::Notification;
+use crate::xml::Element;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+pub const NETCONF_NOTIFICATIONS_NAMESPACE: &str =
+    "urn:ietf:params:xml:ns:yang:ietf-netconf-notifications";
+
+/// Originator of a change, either the server itself or a NETCONF session.
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct ChangedBy {
+    #[serde(default, deserialize_with = "deserialize_empty_leaf")]
+    pub server: bool,
+    pub username: Option<String>,
+    #[serde(rename = "session-id")]
+    pub session_id: Option<u32>,
+    #[serde(rename = "source-host")]
+    pub source_host: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangedDatastore {
+    #[default]
+    Running,
+    Startup,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EditOperation {
+    Merge,
+    Replace,
+    Create,
+    Delete,
+    Remove,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ConfigEdit {
+    /// Instance identifier of the edited node, e.g. `/ex:system/ex:hostname`.
+    pub target: String,
+    /// Namespace declarations in scope of `target`.
+    #[serde(skip)]
+    pub namespaces: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "deserialize_optional_leaf")]
+    pub operation: Option<EditOperation>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ConfigChange {
+    #[serde(rename = "changed-by", default)]
+    pub changed_by: ChangedBy,
+    #[serde(default, deserialize_with = "deserialize_leaf")]
+    pub datastore: ChangedDatastore,
+    #[serde(rename = "edit", default)]
+    pub edits: Vec<ConfigEdit>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct CapabilityChange {
+    #[serde(rename = "changed-by", default)]
+    pub changed_by: ChangedBy,
+    #[serde(rename = "added-capability", default)]
+    pub added_capabilities: Vec<String>,
+    #[serde(rename = "deleted-capability", default)]
+    pub deleted_capabilities: Vec<String>,
+    #[serde(rename = "modified-capability", default)]
+    pub modified_capabilities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SessionStart {
+    pub username: String,
+    #[serde(rename = "session-id")]
+    pub session_id: u32,
+    #[serde(rename = "source-host")]
+    pub source_host: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum TerminationReason {
+    Closed,
+    Killed,
+    Dropped,
+    Timeout,
+    BadHello,
+    Other,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SessionEnd {
+    pub username: String,
+    #[serde(rename = "session-id")]
+    pub session_id: u32,
+    #[serde(rename = "source-host")]
+    pub source_host: Option<String>,
+    #[serde(rename = "killed-by")]
+    pub killed_by: Option<u32>,
+    #[serde(rename = "termination-reason", deserialize_with = "deserialize_leaf")]
+    pub termination_reason: TerminationReason,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmEvent {
+    Start,
+    Cancel,
+    Timeout,
+    Extend,
+    Complete,
+}
+
+/// The session parameters are absent for the `timeout` event.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ConfirmedCommit {
+    pub username: Option<String>,
+    #[serde(rename = "session-id")]
+    pub session_id: Option<u32>,
+    #[serde(rename = "source-host")]
+    pub source_host: Option<String>,
+    #[serde(rename = "confirm-event", deserialize_with = "deserialize_leaf")]
+    pub confirm_event: ConfirmEvent,
+    /// Seconds until the confirmed commit is rolled back, for `start` and
+    /// `extend` events.
+    pub timeout: Option<u32>,
+}
+
+/// RFC 6470 NETCONF base notification.
+#[derive(Debug, PartialEq, Clone)]
+pub enum NetconfNotification {
+    ConfigChange(ConfigChange),
+    CapabilityChange(CapabilityChange),
+    SessionStart(SessionStart),
+    SessionEnd(SessionEnd),
+    ConfirmedCommit(ConfirmedCommit),
+}
+
+impl NetconfNotification {
+    /// Returns `None` for notifications which are not NETCONF base
+    /// notifications.
+    pub fn from_notification(
+        notification: &Notification,
+    ) -> Result<Option<NetconfNotification>, NetconfClientError> {
+        if notification.event_namespace.as_deref() != Some(NETCONF_NOTIFICATIONS_NAMESPACE) {
+            return Ok(None);
+        }
+        let event = notification.event.as_str();
+        let parsed = match notification.event_name.as_str() {
+            "netconf-config-change" => {
+                let mut change: ConfigChange = from_str(event)?;
+                set_edit_namespaces(&mut change, notification)?;
+                NetconfNotification::ConfigChange(change)
+            }
+            "netconf-capability-change" => NetconfNotification::CapabilityChange(from_str(event)?),
+            "netconf-session-start" => NetconfNotification::SessionStart(from_str(event)?),
+            "netconf-session-end" => NetconfNotification::SessionEnd(from_str(event)?),
+            "netconf-confirmed-commit" => NetconfNotification::ConfirmedCommit(from_str(event)?),
+            _ => return Ok(None),
+        };
+        Ok(Some(parsed))
+    }
+}
+
+fn set_edit_namespaces(
+    change: &mut ConfigChange,
+    notification: &Notification,
+) -> Result<(), NetconfClientError> {
+    let root = Element::parse(&notification.raw)?;
+    let event = match root.child(&notification.event_name) {
+        Some(event) => event,
+        None => return Ok(()),
+    };
+    let mut scope = root.declarations.clone();
+    scope.extend(event.declarations.clone());
+    for (edit, element) in change.edits.iter_mut().zip(event.children_named("edit")) {
+        let mut namespaces = scope.clone();
+        namespaces.extend(element.declarations.clone());
+        if let Some(target) = element.child("target") {
+            namespaces.extend(target.declarations.clone());
+        }
+        edit.namespaces = namespaces;
+    }
+    Ok(())
+}
+
+/// Enumeration leaf, which quick-xml only deserializes from `$value`.
+#[derive(Deserialize)]
+struct Leaf<T> {
+    #[serde(rename = "$value")]
+    value: T,
+}
+
+fn deserialize_leaf<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Leaf::deserialize(deserializer).map(|leaf| leaf.value)
+}
+
+fn deserialize_optional_leaf<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserialize_leaf(deserializer).map(Some)
+}
+
+fn deserialize_empty_leaf<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    serde::de::IgnoredAny::deserialize(deserializer)?;
+    Ok(true)
+}
+
+fn from_str<T: serde::de::DeserializeOwned>(event: &str) -> Result<T, NetconfClientError> {
+    Ok(quick_xml::de::from_str(event)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::notification;
+
+    fn parse(event: &str) -> NetconfNotification {
+        NetconfNotification::from_notification(&notification("2021-06-01T10:00:00Z", event))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn config_change() {
+        let change = parse(
+            r#"<netconf-config-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications">
+    <changed-by>
+        <username>fred</username>
+        <session-id>17</session-id>
+        <source-host>10.0.0.1</source-host>
+    </changed-by>
+    <datastore>running</datastore>
+    <edit>
+        <target xmlns:ex="http://example.com/system">/ex:system/ex:hostname</target>
+        <operation>replace</operation>
+    </edit>
+    <edit>
+        <target xmlns:ex="http://example.com/system">/ex:system/ex:ntp</target>
+    </edit>
+</netconf-config-change>"#,
+        );
+        let change = match change {
+            NetconfNotification::ConfigChange(change) => change,
+            change => panic!("Unexpected notification {:?}", change),
+        };
+        assert_eq!(
+            change.changed_by,
+            ChangedBy {
+                server: false,
+                username: Some("fred".to_string()),
+                session_id: Some(17),
+                source_host: Some("10.0.0.1".to_string()),
+            }
+        );
+        assert_eq!(change.datastore, ChangedDatastore::Running);
+        assert_eq!(change.edits.len(), 2);
+        assert_eq!(change.edits[0].target, "/ex:system/ex:hostname");
+        assert_eq!(change.edits[0].operation, Some(EditOperation::Replace));
+        assert_eq!(
+            change.edits[0].namespaces.get("ex").map(String::as_str),
+            Some("http://example.com/system")
+        );
+        assert_eq!(change.edits[1].operation, None);
+
+        match parse(
+            r#"<netconf-config-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"><changed-by><server/></changed-by><datastore>startup</datastore></netconf-config-change>"#,
+        ) {
+            NetconfNotification::ConfigChange(change) => {
+                assert!(change.changed_by.server);
+                assert_eq!(change.datastore, ChangedDatastore::Startup);
+                assert!(change.edits.is_empty());
+            }
+            change => panic!("Unexpected notification {:?}", change),
+        }
+    }
+
+    #[test]
+    fn capability_change() {
+        assert_eq!(
+            parse(
+                r#"<netconf-capability-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications">
+    <changed-by><server/></changed-by>
+    <added-capability>urn:ietf:params:netconf:capability:candidate:1.0</added-capability>
+    <deleted-capability>urn:ietf:params:netconf:capability:startup:1.0</deleted-capability>
+</netconf-capability-change>"#
+            ),
+            NetconfNotification::CapabilityChange(CapabilityChange {
+                changed_by: ChangedBy {
+                    server: true,
+                    ..Default::default()
+                },
+                added_capabilities: vec![
+                    "urn:ietf:params:netconf:capability:candidate:1.0".to_string()
+                ],
+                deleted_capabilities: vec![
+                    "urn:ietf:params:netconf:capability:startup:1.0".to_string()
+                ],
+                modified_capabilities: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn session_events() {
+        assert_eq!(
+            parse(
+                r#"<netconf-session-start xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"><username>fred</username><session-id>17</session-id><source-host>10.0.0.1</source-host></netconf-session-start>"#
+            ),
+            NetconfNotification::SessionStart(SessionStart {
+                username: "fred".to_string(),
+                session_id: 17,
+                source_host: Some("10.0.0.1".to_string()),
+            })
+        );
+        assert_eq!(
+            parse(
+                r#"<netconf-session-end xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"><username>fred</username><session-id>17</session-id><killed-by>5</killed-by><termination-reason>killed</termination-reason></netconf-session-end>"#
+            ),
+            NetconfNotification::SessionEnd(SessionEnd {
+                username: "fred".to_string(),
+                session_id: 17,
+                source_host: None,
+                killed_by: Some(5),
+                termination_reason: TerminationReason::Killed,
+            })
+        );
+        assert_eq!(
+            parse(
+                r#"<netconf-confirmed-commit xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"><confirm-event>timeout</confirm-event></netconf-confirmed-commit>"#
+            ),
+            NetconfNotification::ConfirmedCommit(ConfirmedCommit {
+                username: None,
+                session_id: None,
+                source_host: None,
+                confirm_event: ConfirmEvent::Timeout,
+                timeout: None,
+            })
+        );
+
+        let other = notification(
+            "2021-06-01T10:00:00Z",
+            r#"<event xmlns="http://example.com/events"/>"#,
+        );
+        assert_eq!(
+            NetconfNotification::from_notification(&other).unwrap(),
+            None
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::models::subscribed_notifications::SubscriptionId;
+use crate::xml::Element;
 use serde::Deserialize;
 
 pub trait RpcRsp {
@@ -24,7 +26,7 @@ impl RpcRsp for EditConfigRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -50,7 +52,7 @@ impl RpcRsp for CopyConfigRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -76,7 +78,7 @@ impl RpcRsp for DeleteConfigRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -102,7 +104,7 @@ impl RpcRsp for LockRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -128,7 +130,7 @@ impl RpcRsp for UnlockRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -154,7 +156,7 @@ impl RpcRsp for CloseSessionRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -180,7 +182,7 @@ impl RpcRsp for KillSessionRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -205,7 +207,7 @@ impl RpcRsp for HelloServer {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -223,6 +225,10 @@ pub struct GetConfigRsp {
     pub rpc_error: Option<Vec<RpcError>>,
     #[serde(skip)]
     pub data: Option<String>,
+    /// Parsed data in the `report-all-tagged` with-defaults mode, where
+    /// defaulted nodes are found with [`Element::is_default`].
+    #[serde(skip)]
+    pub data_nodes: Option<Vec<Element>>,
 }
 
 impl RpcRsp for GetConfigRsp {
@@ -231,7 +237,7 @@ impl RpcRsp for GetConfigRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -249,6 +255,10 @@ pub struct GetRsp {
     pub rpc_error: Option<Vec<RpcError>>,
     #[serde(skip)]
     pub data: Option<String>,
+    /// Parsed data in the `report-all-tagged` with-defaults mode, where
+    /// defaulted nodes are found with [`Element::is_default`].
+    #[serde(skip)]
+    pub data_nodes: Option<Vec<Element>>,
 }
 
 impl RpcRsp for GetRsp {
@@ -257,7 +267,63 @@ impl RpcRsp for GetRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct GetDataRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+    #[serde(skip)]
+    pub data: Option<String>,
+    /// Parsed data in the `report-all-tagged` with-defaults mode, where
+    /// defaulted nodes are found with [`Element::is_default`].
+    #[serde(skip)]
+    pub data_nodes: Option<Vec<Element>>,
+}
+
+impl RpcRsp for GetDataRsp {
+    fn is_ok(&self) -> bool {
+        self.rpc_error.is_none()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct EditDataRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for EditDataRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -283,7 +349,7 @@ impl RpcRsp for DiscardChangesRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -309,7 +375,270 @@ impl RpcRsp for CommitRsp {
     }
 
     fn get_error(&self) -> Option<&Vec<RpcError>> {
-        return Some(self.rpc_error.as_ref().unwrap());
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct GetSchemaRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+    #[serde(skip)]
+    pub schema: String,
+}
+
+impl RpcRsp for GetSchemaRsp {
+    fn is_ok(&self) -> bool {
+        self.rpc_error.is_none()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct PartialLockRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "lock-id")]
+    pub lock_id: Option<u32>,
+    #[serde(rename = "locked-node", default)]
+    pub locked_nodes: Vec<String>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for PartialLockRsp {
+    fn is_ok(&self) -> bool {
+        self.rpc_error.is_none()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct PartialUnlockRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for PartialUnlockRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CancelCommitRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for CancelCommitRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CreateSubscriptionRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for CreateSubscriptionRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct EstablishSubscriptionRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub id: Option<SubscriptionId>,
+    #[serde(rename = "replay-start-time-revision")]
+    pub replay_start_time_revision: Option<String>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for EstablishSubscriptionRsp {
+    fn is_ok(&self) -> bool {
+        self.rpc_error.is_none()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct ModifySubscriptionRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for ModifySubscriptionRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct DeleteSubscriptionRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for DeleteSubscriptionRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct KillSubscriptionRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for KillSubscriptionRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct ValidateRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
+impl RpcRsp for ValidateRsp {
+    fn is_ok(&self) -> bool {
+        self.ok.is_some()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
     }
 
     fn get_message_id(&self) -> Option<u32> {
@@ -317,6 +646,44 @@ impl RpcRsp for CommitRsp {
     }
 }
 
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct RawRpcRsp {
+    #[serde(rename = "xmlns")]
+    pub xmlns: String,
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    #[serde(rename = "ok")]
+    pub ok: Option<()>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+    #[serde(skip)]
+    pub reply: String,
+}
+
+impl RpcRsp for RawRpcRsp {
+    fn is_ok(&self) -> bool {
+        self.rpc_error.is_none()
+    }
+
+    fn get_error(&self) -> Option<&Vec<RpcError>> {
+        Some(self.rpc_error.as_ref().unwrap())
+    }
+
+    fn get_message_id(&self) -> Option<u32> {
+        Some(self.message_id)
+    }
+}
+
+/// `<rpc-reply>` which matches no request. Replies to a malformed message
+/// carry no message-id.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct UnmatchedRpcRsp {
+    #[serde(rename = "message-id")]
+    pub message_id: Option<u32>,
+    #[serde(rename = "rpc-error")]
+    pub rpc_error: Option<Vec<RpcError>>,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct RpcError {
     #[serde(rename = "error-type")]
@@ -343,6 +710,35 @@ pub struct ErrorInfo {
     pub bad_attribute: Option<String>,
     #[serde(rename = "bad-element")]
     pub bad_element: Option<String>,
+    #[serde(rename = "lock-id", default)]
+    pub lock_ids: Vec<u32>,
+}
+
+/// Holder of the lock which caused a `lock-denied` error. Session id 0 means
+/// the lock is held by a non-NETCONF entity.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LockDenied {
+    pub session_id: Option<u32>,
+    pub lock_ids: Vec<u32>,
+}
+
+impl RpcError {
+    pub fn lock_denied(&self) -> Option<LockDenied> {
+        if self.error_tag.value != ErrorTagE::LockDenied {
+            return None;
+        }
+        Some(LockDenied {
+            session_id: self
+                .error_info
+                .as_ref()
+                .and_then(|error_info| error_info.session_id),
+            lock_ids: self
+                .error_info
+                .as_ref()
+                .map(|error_info| error_info.lock_ids.clone())
+                .unwrap_or_default(),
+        })
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -482,6 +878,7 @@ mod tests {
                 session_id: None,
                 bad_attribute: Some("message-id".to_string()),
                 bad_element: Some("rpc".to_string()),
+                lock_ids: Vec::new(),
             }),
         };
         assert_eq!(reply, expected_reply);
@@ -510,6 +907,7 @@ mod tests {
             message_id: 101,
             rpc_error: None,
             data: None,
+            data_nodes: None,
         };
         assert!(reply.is_ok());
         assert_eq!(reply, expected_reply);
@@ -677,6 +1075,43 @@ mod tests {
         assert!(reply.is_ok());
     }
 
+    #[test]
+    fn raw_rpc_rsp() {
+        let xml = r#"
+<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+    <rpc-error>
+        <error-type>protocol</error-type>
+        <error-tag>operation-not-supported</error-tag>
+        <error-severity>error</error-severity>
+    </rpc-error>
+</rpc-reply>
+"#;
+        let reply: RawRpcRsp = from_str(xml).unwrap();
+        let expected_reply = RawRpcRsp {
+            xmlns: "urn:ietf:params:xml:ns:netconf:base:1.0".to_string(),
+            message_id: 101,
+            ok: None,
+            rpc_error: Some(vec![RpcError {
+                error_type: ErrorType {
+                    value: ErrorTypeE::Protocol,
+                },
+                error_tag: ErrorTag {
+                    value: ErrorTagE::OperationNotSupported,
+                },
+                error_severity: ErrorSeverity {
+                    value: ErrorSeverityE::Error,
+                },
+                error_app_tag: None,
+                error_path: None,
+                error_message: None,
+                error_info: None,
+            }]),
+            reply: "".to_string(),
+        };
+        assert_eq!(reply, expected_reply);
+        assert!(!reply.is_ok());
+    }
+
     #[test]
     fn get_config_rsp() {
         let xml = r#"
@@ -704,8 +1139,69 @@ mod tests {
             message_id: 101,
             rpc_error: None,
             data: None,
+            data_nodes: None,
         };
         assert_eq!(reply, expected_reply);
         assert!(reply.is_ok());
     }
+
+    #[test]
+    fn partial_lock_rsp() {
+        let xml = r#"
+<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+    <lock-id xmlns="urn:ietf:params:xml:ns:netconf:partial-lock:1.0">127</lock-id>
+    <locked-node xmlns="urn:ietf:params:xml:ns:netconf:partial-lock:1.0">/t:users/t:user[t:name="Alice"]</locked-node>
+    <locked-node xmlns="urn:ietf:params:xml:ns:netconf:partial-lock:1.0">/t:users/t:user[t:name="Bob"]</locked-node>
+</rpc-reply>
+"#;
+        let reply: PartialLockRsp = from_str(xml).unwrap();
+        assert!(reply.is_ok());
+        assert_eq!(reply.lock_id, Some(127));
+        assert_eq!(reply.locked_nodes.len(), 2);
+        assert_eq!(reply.locked_nodes[1], r#"/t:users/t:user[t:name="Bob"]"#);
+    }
+
+    #[test]
+    fn establish_subscription_rsp() {
+        let xml = r#"
+<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+    <id xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications">52</id>
+    <replay-start-time-revision xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications">2021-06-01T10:00:00Z</replay-start-time-revision>
+</rpc-reply>
+"#;
+        let reply: EstablishSubscriptionRsp = from_str(xml).unwrap();
+        assert!(reply.is_ok());
+        assert_eq!(reply.id, Some(SubscriptionId(52)));
+        assert_eq!(
+            reply.replay_start_time_revision.as_deref(),
+            Some("2021-06-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn lock_denied_error() {
+        let xml = r#"
+<rpc-reply message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+    <rpc-error>
+        <error-type>protocol</error-type>
+        <error-tag>lock-denied</error-tag>
+        <error-severity>error</error-severity>
+        <error-info>
+            <session-id>4</session-id>
+            <lock-id>127</lock-id>
+            <lock-id>128</lock-id>
+        </error-info>
+    </rpc-error>
+</rpc-reply>
+"#;
+        let reply: PartialLockRsp = from_str(xml).unwrap();
+        assert!(!reply.is_ok());
+        assert_eq!(
+            reply.get_error().unwrap()[0].lock_denied(),
+            Some(LockDenied {
+                session_id: Some(4),
+                lock_ids: vec![127, 128],
+            })
+        );
+    }
 }
@@ -1,5 +1,8 @@
+use crate::capabilities::WithDefaultsMode;
+use crate::consts;
 use heck::KebabCase;
 use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::string::ToString;
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -37,6 +40,8 @@ pub struct GetConfigReq {
 pub struct GetConfig {
     pub source: Target,
     pub filter: Option<Filter>,
+    #[serde(rename = "with-defaults")]
+    pub with_defaults: Option<WithDefaultsParameter>,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -58,7 +63,8 @@ pub struct EditConfig {
     pub test_option: Option<TestOption>,
     #[serde(rename = "error-option")]
     pub error_option: Option<ErrorOption>,
-    pub config: Data,
+    pub config: Option<Data>,
+    pub url: Option<Url>,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -67,7 +73,7 @@ pub struct DefaultOperation {
     pub value: DefaultOperationType,
 }
 
-#[derive(strum_macros::ToString, Debug, Serialize, PartialEq)]
+#[derive(strum_macros::Display, Debug, Serialize, PartialEq)]
 pub enum DefaultOperationType {
     Merge,
     Replace,
@@ -80,7 +86,7 @@ pub struct TestOption {
     pub value: TestOptionType,
 }
 
-#[derive(strum_macros::ToString, Debug, Serialize, PartialEq)]
+#[derive(strum_macros::Display, Debug, Serialize, PartialEq)]
 pub enum TestOptionType {
     TestThenSet,
     Set,
@@ -93,7 +99,7 @@ pub struct ErrorOption {
     pub value: ErrorOptionType,
 }
 
-#[derive(strum_macros::ToString, Debug, Serialize, PartialEq)]
+#[derive(strum_macros::Display, Debug, Serialize, PartialEq)]
 pub enum ErrorOptionType {
     StopOnError,
     ContinueOnError,
@@ -120,140 +126,926 @@ pub struct DeleteConfigReq {
 
 #[derive(Debug, Serialize, PartialEq)]
 pub struct DeleteConfig {
+    pub target: ConfigTargetType,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct GetReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    pub get: Get,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Get {
+    pub filter: Option<Filter>,
+    #[serde(rename = "with-defaults")]
+    pub with_defaults: Option<WithDefaultsParameter>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct DiscardChangesReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "discard-changes")]
+    pub discard_changes: DiscardChanges,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct DiscardChanges {}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct CommitReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    pub commit: Commit,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct Commit {
+    pub confirmed: Option<Confirmed>,
+    #[serde(rename = "confirm-timeout")]
+    pub confirm_timeout: Option<ConfirmTimeout>,
+    pub persist: Option<Persist>,
+    #[serde(rename = "persist-id")]
+    pub persist_id: Option<PersistId>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct Confirmed {}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ConfirmTimeout {
+    #[serde(rename = "$value")]
+    pub value: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Persist {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PersistId {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct CancelCommitReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "cancel-commit")]
+    pub cancel_commit: CancelCommit,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct CancelCommit {
+    #[serde(rename = "persist-id")]
+    pub persist_id: Option<PersistId>,
+}
+
+/// Filter of `<get>` and `<get-config>`. XPath expressions may use the
+/// prefixes declared in `namespaces`.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct Filter {
+    #[serde(rename = "type", serialize_with = "serialize_as_string_kebab_case")]
+    pub filter_type: FilterType,
+    pub select: Option<String>,
+    #[serde(rename = "$value")]
+    pub data: String,
+    #[serde(skip)]
+    pub namespaces: BTreeMap<String, String>,
+}
+
+impl Filter {
+    pub fn subtree(data: &str) -> Filter {
+        Filter {
+            filter_type: FilterType::Subtree,
+            select: None,
+            data: data.to_string(),
+            namespaces: BTreeMap::new(),
+        }
+    }
+
+    pub fn xpath(select: &str, namespaces: BTreeMap<String, String>) -> Filter {
+        Filter {
+            filter_type: FilterType::XPath,
+            select: Some(select.to_string()),
+            data: String::new(),
+            namespaces,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct LockReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    pub lock: Lock,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Lock {
+    pub target: Target,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct UnlockReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    pub unlock: Unlock,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Unlock {
     pub target: Target,
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "rpc")]
-pub struct GetReq {
-    #[serde(rename = "message-id")]
-    pub message_id: u32,
-    pub xmlns: String,
-    pub get: Get,
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct CloseSessionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "close-session")]
+    pub close_session: CloseSession,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct CloseSession {}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct KillSessionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "kill-session")]
+    pub kill_session: KillSession,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct KillSession {
+    #[serde(rename = "session-id")]
+    pub session_id: SessionId,
+}
+
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct SessionId {
+    #[serde(rename = "$value")]
+    pub value: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct CopyConfigReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "copy-config")]
+    pub copy_config: CopyConfig,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CopyConfig {
+    pub target: ConfigTargetType,
+    pub source: CopyConfigSourceType,
+    #[serde(rename = "with-defaults")]
+    pub with_defaults: Option<WithDefaultsParameter>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum CopyConfigSourceType {
+    Datastore { source: DatastoreType },
+    Config { config: Data },
+    Url { url: Url },
+}
+
+/// Target of `<copy-config>` and `<delete-config>`. A URL needs the `:url`
+/// capability with a matching scheme.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum ConfigTargetType {
+    Datastore { target: DatastoreType },
+    Url { url: Url },
+}
+
+impl From<DatastoreType> for ConfigTargetType {
+    fn from(target: DatastoreType) -> Self {
+        ConfigTargetType::Datastore { target }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Url {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+impl Url {
+    pub fn new(value: &str) -> Url {
+        Url {
+            value: value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct PartialLockReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "partial-lock")]
+    pub partial_lock: PartialLock,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PartialLock {
+    pub xmlns: String,
+    pub select: Vec<Select>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Select {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct PartialUnlockReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "partial-unlock")]
+    pub partial_unlock: PartialUnlock,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PartialUnlock {
+    pub xmlns: String,
+    #[serde(rename = "lock-id")]
+    pub lock_id: LockId,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LockId {
+    #[serde(rename = "$value")]
+    pub value: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct GetSchemaReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "get-schema")]
+    pub get_schema: GetSchema,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct GetSchema {
+    pub xmlns: String,
+    pub identifier: Identifier,
+    pub version: Option<Version>,
+    pub format: Option<Format>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Identifier {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Version {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Format {
+    #[serde(rename = "$value", serialize_with = "serialize_as_string_kebab_case")]
+    pub value: SchemaFormat,
+}
+
+#[derive(strum_macros::Display, Debug, Serialize, PartialEq, Clone, Copy)]
+pub enum SchemaFormat {
+    Xsd,
+    Yang,
+    Yin,
+    Rng,
+    Rnc,
+}
+
+impl SchemaFormat {
+    pub fn extension(&self) -> String {
+        self.to_string().to_kebab_case()
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct ValidateReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    pub validate: Validate,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Validate {
+    pub source: ValidateSourceType,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum ValidateSourceType {
+    Datastore { source: DatastoreType },
+    Config { config: Data },
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct RawRpcReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "$value")]
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct CreateSubscriptionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "create-subscription")]
+    pub create_subscription: CreateSubscription,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CreateSubscription {
+    pub xmlns: String,
+    pub stream: Option<Stream>,
+    pub filter: Option<SubscriptionFilter>,
+    #[serde(rename = "startTime")]
+    pub start_time: Option<StartTime>,
+    #[serde(rename = "stopTime")]
+    pub stop_time: Option<StopTime>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Stream {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SubscriptionFilter {
+    #[serde(rename = "type")]
+    pub filter_type: String,
+    pub select: Option<String>,
+    #[serde(rename = "$value")]
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StartTime {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StopTime {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+/// Event filter of `<create-subscription>`. XPath expressions may use the
+/// prefixes declared in `namespaces`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum NotificationFilter {
+    Subtree(String),
+    XPath {
+        select: String,
+        namespaces: BTreeMap<String, String>,
+    },
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct EstablishSubscriptionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "establish-subscription")]
+    pub establish_subscription: EstablishSubscription,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct EstablishSubscription {
+    pub xmlns: String,
+    #[serde(rename = "xmlns:yp")]
+    pub xmlns_yp: Option<String>,
+    pub stream: Option<Stream>,
+    #[serde(rename = "stream-subtree-filter")]
+    pub stream_subtree_filter: Option<StreamFilter>,
+    #[serde(rename = "stream-xpath-filter")]
+    pub stream_xpath_filter: Option<StreamFilter>,
+    #[serde(rename = "replay-start-time")]
+    pub replay_start_time: Option<ReplayStartTime>,
+    #[serde(rename = "yp:datastore")]
+    pub datastore: Option<PushDatastore>,
+    #[serde(rename = "yp:datastore-subtree-filter")]
+    pub datastore_subtree_filter: Option<StreamFilter>,
+    #[serde(rename = "yp:datastore-xpath-filter")]
+    pub datastore_xpath_filter: Option<StreamFilter>,
+    #[serde(rename = "yp:periodic")]
+    pub periodic: Option<Periodic>,
+    #[serde(rename = "yp:on-change")]
+    pub on_change: Option<OnChange>,
+    #[serde(rename = "stop-time")]
+    pub stop_time: Option<StopTime>,
+    pub dscp: Option<Dscp>,
+    pub encoding: Option<Encoding>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StreamFilter {
+    #[serde(rename = "$value")]
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ReplayStartTime {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Dscp {
+    #[serde(rename = "$value")]
+    pub value: u8,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Encoding {
+    #[serde(rename = "$value", serialize_with = "serialize_as_string_kebab_case")]
+    pub value: SubscriptionEncoding,
+}
+
+#[derive(strum_macros::Display, Debug, Serialize, PartialEq, Clone, Copy)]
+pub enum SubscriptionEncoding {
+    EncodeXml,
+    EncodeJson,
+}
+
+/// Dynamic subscription to an event stream (RFC 8639).
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StreamSubscription {
+    pub stream: String,
+    pub filter: Option<NotificationFilter>,
+    pub replay_start_time: Option<String>,
+    pub stop_time: Option<String>,
+    pub encoding: Option<SubscriptionEncoding>,
+    pub dscp: Option<u8>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PushDatastore {
+    #[serde(rename = "xmlns:ds")]
+    pub xmlns_ds: String,
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Periodic {
+    #[serde(rename = "yp:period")]
+    pub period: Period,
+    #[serde(rename = "yp:anchor-time")]
+    pub anchor_time: Option<AnchorTime>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Period {
+    #[serde(rename = "$value")]
+    pub value: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct AnchorTime {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct OnChange {
+    #[serde(rename = "yp:dampening-period")]
+    pub dampening_period: Option<DampeningPeriod>,
+    #[serde(rename = "yp:sync-on-start")]
+    pub sync_on_start: Option<SyncOnStart>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DampeningPeriod {
+    #[serde(rename = "$value")]
+    pub value: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SyncOnStart {
+    #[serde(rename = "$value")]
+    pub value: bool,
+}
+
+/// YANG-Push (RFC 8641) subscription to a datastore, e.g. `operational`
+/// from ietf-datastores.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DatastoreSubscription {
+    pub datastore: String,
+    pub filter: Option<NotificationFilter>,
+    pub trigger: UpdateTrigger,
+    pub stop_time: Option<String>,
+    pub encoding: Option<SubscriptionEncoding>,
+    pub dscp: Option<u8>,
+}
+
+/// Periods are in centiseconds.
+#[derive(Debug, PartialEq, Clone)]
+pub enum UpdateTrigger {
+    Periodic {
+        period: u32,
+        anchor_time: Option<String>,
+    },
+    OnChange {
+        dampening_period: Option<u32>,
+        sync_on_start: Option<bool>,
+    },
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct ModifySubscriptionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "modify-subscription")]
+    pub modify_subscription: ModifySubscription,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ModifySubscription {
+    pub xmlns: String,
+    pub id: Id,
+    #[serde(rename = "stream-subtree-filter")]
+    pub stream_subtree_filter: Option<StreamFilter>,
+    #[serde(rename = "stream-xpath-filter")]
+    pub stream_xpath_filter: Option<StreamFilter>,
+    #[serde(rename = "stop-time")]
+    pub stop_time: Option<StopTime>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Id {
+    #[serde(rename = "$value")]
+    pub value: u32,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct DeleteSubscriptionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "delete-subscription")]
+    pub delete_subscription: SubscriptionRef,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename = "rpc")]
+pub struct KillSubscriptionReq {
+    #[serde(rename = "message-id")]
+    pub message_id: u32,
+    pub xmlns: String,
+    #[serde(rename = "kill-subscription")]
+    pub kill_subscription: SubscriptionRef,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SubscriptionRef {
+    pub xmlns: String,
+    pub id: Id,
+}
+
+/// YANG identity of a datastore derived from ietf-datastores `datastore`.
+/// Vendor datastores are created with [`Datastore::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datastore {
+    pub namespace: String,
+    pub name: String,
+}
+
+impl Datastore {
+    pub fn new(namespace: &str, name: &str) -> Datastore {
+        Datastore {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn running() -> Datastore {
+        Datastore::new(consts::DATASTORES_XMLNS, "running")
+    }
+
+    pub fn candidate() -> Datastore {
+        Datastore::new(consts::DATASTORES_XMLNS, "candidate")
+    }
+
+    pub fn startup() -> Datastore {
+        Datastore::new(consts::DATASTORES_XMLNS, "startup")
+    }
+
+    pub fn intended() -> Datastore {
+        Datastore::new(consts::DATASTORES_XMLNS, "intended")
+    }
+
+    pub fn operational() -> Datastore {
+        Datastore::new(consts::DATASTORES_XMLNS, "operational")
+    }
+}
+
+impl From<DatastoreType> for Datastore {
+    fn from(datastore: DatastoreType) -> Self {
+        match datastore {
+            DatastoreType::Running => Datastore::running(),
+            DatastoreType::Candidate => Datastore::candidate(),
+            DatastoreType::Startup => Datastore::startup(),
+        }
+    }
+}
+
+/// YANG identity of a data origin derived from ietf-origin `origin`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub namespace: String,
+    pub name: String,
+}
+
+impl Origin {
+    pub fn new(namespace: &str, name: &str) -> Origin {
+        Origin {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn intended() -> Origin {
+        Origin::new(consts::ORIGIN_XMLNS, "intended")
+    }
+
+    pub fn dynamic() -> Origin {
+        Origin::new(consts::ORIGIN_XMLNS, "dynamic")
+    }
+
+    pub fn system() -> Origin {
+        Origin::new(consts::ORIGIN_XMLNS, "system")
+    }
+
+    pub fn learned() -> Origin {
+        Origin::new(consts::ORIGIN_XMLNS, "learned")
+    }
+
+    /// `or:default`, for values taken from the YANG default.
+    pub fn default_value() -> Origin {
+        Origin::new(consts::ORIGIN_XMLNS, "default")
+    }
+
+    pub fn unknown() -> Origin {
+        Origin::new(consts::ORIGIN_XMLNS, "unknown")
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum OriginFilter {
+    /// Nodes with one of the origins.
+    Include(Vec<Origin>),
+    /// Nodes with none of the origins.
+    Exclude(Vec<Origin>),
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-pub struct Get {
+/// Optional parameters of RFC 8526 `<get-data>`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct GetDataOptions {
     pub filter: Option<Filter>,
+    /// `true` selects only configuration, `false` only state nodes.
+    pub config_filter: Option<bool>,
+    pub origin_filter: Option<OriginFilter>,
+    /// Unbounded when not set.
+    pub max_depth: Option<u16>,
+    pub with_origin: bool,
+    pub with_defaults: Option<WithDefaultsMode>,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
 #[serde(rename = "rpc")]
-pub struct DiscardChangesReq {
+pub struct GetDataReq {
     #[serde(rename = "message-id")]
     pub message_id: u32,
     pub xmlns: String,
-    #[serde(rename = "discard-changes")]
-    pub discard_changes: DiscardChanges,
+    #[serde(rename = "get-data")]
+    pub get_data: GetData,
 }
 
-#[derive(Debug, Serialize, PartialEq, Default)]
-pub struct DiscardChanges {}
-
 #[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "rpc")]
-pub struct CommitReq {
-    #[serde(rename = "message-id")]
-    pub message_id: u32,
+pub struct GetData {
     pub xmlns: String,
-    pub commit: Commit,
+    pub datastore: DatastoreIdentity,
+    #[serde(rename = "subtree-filter")]
+    pub subtree_filter: Option<StreamFilter>,
+    #[serde(rename = "xpath-filter")]
+    pub xpath_filter: Option<StreamFilter>,
+    #[serde(rename = "config-filter")]
+    pub config_filter: Option<ConfigFilter>,
+    #[serde(rename = "origin-filter")]
+    pub origin_filter: Vec<OriginIdentity>,
+    #[serde(rename = "negated-origin-filter")]
+    pub negated_origin_filter: Vec<OriginIdentity>,
+    #[serde(rename = "max-depth")]
+    pub max_depth: Option<MaxDepth>,
+    #[serde(rename = "with-origin")]
+    pub with_origin: Option<WithOrigin>,
+    #[serde(rename = "with-defaults")]
+    pub with_defaults: Option<WithDefaultsParameter>,
 }
 
-#[derive(Debug, Serialize, PartialEq, Default)]
-pub struct Commit {}
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct Filter {
-    #[serde(rename = "type", serialize_with = "serialize_as_string_kebab_case")]
-    pub filter_type: FilterType,
+/// Identity value, declaring the namespace of its `ds` prefix.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DatastoreIdentity {
+    #[serde(rename = "xmlns:ds")]
+    pub xmlns_ds: String,
     #[serde(rename = "$value")]
-    pub data: String,
+    pub value: String,
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "rpc")]
-pub struct LockReq {
-    #[serde(rename = "message-id")]
-    pub message_id: u32,
-    pub xmlns: String,
-    pub lock: Lock,
+impl From<Datastore> for DatastoreIdentity {
+    fn from(datastore: Datastore) -> Self {
+        DatastoreIdentity {
+            xmlns_ds: datastore.namespace,
+            value: format!("ds:{}", datastore.name),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq)]
-pub struct Lock {
-    pub target: Target,
+pub struct OriginIdentity {
+    #[serde(rename = "xmlns:or")]
+    pub xmlns_or: String,
+    #[serde(rename = "$value")]
+    pub value: String,
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "rpc")]
-pub struct UnlockReq {
-    #[serde(rename = "message-id")]
-    pub message_id: u32,
-    pub xmlns: String,
-    pub unlock: Unlock,
+impl From<Origin> for OriginIdentity {
+    fn from(origin: Origin) -> Self {
+        OriginIdentity {
+            xmlns_or: origin.namespace,
+            value: format!("or:{}", origin.name),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq)]
-pub struct Unlock {
-    pub target: Target,
+pub struct ConfigFilter {
+    #[serde(rename = "$value")]
+    pub value: bool,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "rpc")]
-pub struct CloseSessionReq {
-    #[serde(rename = "message-id")]
-    pub message_id: u32,
-    pub xmlns: String,
-    #[serde(rename = "close-session")]
-    pub close_session: CloseSession,
+pub struct MaxDepth {
+    #[serde(rename = "$value")]
+    pub value: u16,
 }
 
 #[derive(Debug, Serialize, PartialEq, Default)]
-pub struct CloseSession {}
+pub struct WithOrigin {}
 
 #[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "rpc")]
-pub struct KillSessionReq {
-    #[serde(rename = "message-id")]
-    pub message_id: u32,
+pub struct WithDefaultsParameter {
     pub xmlns: String,
-    #[serde(rename = "kill-session")]
-    pub kill_session: KillSession,
-}
-
-#[derive(Debug, Serialize, PartialEq, Default)]
-pub struct KillSession {
-    #[serde(rename = "session-id")]
-    pub session_id: SessionId,
+    #[serde(rename = "$value", serialize_with = "serialize_as_string_kebab_case")]
+    pub value: WithDefaultsMode,
 }
 
-#[derive(Debug, Serialize, PartialEq, Default)]
-pub struct SessionId {
-    #[serde(rename = "$value")]
-    pub value: u32,
+impl From<WithDefaultsMode> for WithDefaultsParameter {
+    fn from(value: WithDefaultsMode) -> Self {
+        WithDefaultsParameter {
+            xmlns: consts::WITH_DEFAULTS_XMLNS.to_string(),
+            value,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq)]
 #[serde(rename = "rpc")]
-pub struct CopyConfigReq {
+pub struct EditDataReq {
     #[serde(rename = "message-id")]
     pub message_id: u32,
     pub xmlns: String,
-    #[serde(rename = "copy-config")]
-    pub copy_config: CopyConfig,
+    #[serde(rename = "edit-data")]
+    pub edit_data: EditData,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
-pub struct CopyConfig {
-    pub target: Target,
-    pub source: CopyConfigSourceType,
+pub struct EditData {
+    pub xmlns: String,
+    pub datastore: DatastoreIdentity,
+    #[serde(rename = "default-operation")]
+    pub default_operation: Option<DefaultOperation>,
+    pub config: Data,
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(untagged)]
-pub enum CopyConfigSourceType {
-    Datastore { source: DatastoreType },
-    Config { config: Data },
+/// Path to the data node on which a YANG 1.1 action is invoked, e.g.
+/// `ActionPath::new(ns, "interfaces").node("interface").key("name", "eth0")`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ActionPath {
+    pub nodes: Vec<PathNode>,
+}
+
+/// Nodes without a namespace are in the namespace of their parent. List keys
+/// are given in the order of the `key` statement.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PathNode {
+    pub namespace: Option<String>,
+    pub name: String,
+    pub keys: Vec<(String, String)>,
+}
+
+impl ActionPath {
+    pub fn new(namespace: &str, name: &str) -> ActionPath {
+        ActionPath {
+            nodes: vec![PathNode {
+                namespace: Some(namespace.to_string()),
+                name: name.to_string(),
+                keys: Vec::new(),
+            }],
+        }
+    }
+
+    pub fn node(mut self, name: &str) -> ActionPath {
+        self.nodes.push(PathNode {
+            namespace: None,
+            name: name.to_string(),
+            keys: Vec::new(),
+        });
+        self
+    }
+
+    /// Node augmented from another module.
+    pub fn augmented_node(mut self, namespace: &str, name: &str) -> ActionPath {
+        self.nodes.push(PathNode {
+            namespace: Some(namespace.to_string()),
+            name: name.to_string(),
+            keys: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds a key to the last node.
+    pub fn key(mut self, name: &str, value: &str) -> ActionPath {
+        if let Some(node) = self.nodes.last_mut() {
+            node.keys.push((name.to_string(), value.to_string()));
+        }
+        self
+    }
+
+    /// Namespace of the last node, which is the namespace of the action
+    /// unless it is augmented.
+    pub fn namespace(&self) -> &str {
+        self.nodes
+            .iter()
+            .rev()
+            .find_map(|node| node.namespace.as_deref())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -269,9 +1061,11 @@ pub enum DatastoreType {
     Startup,
 }
 
-#[derive(strum_macros::ToString, Debug, PartialEq, Clone)]
+#[derive(strum_macros::Display, Debug, PartialEq, Clone)]
 pub enum FilterType {
     Subtree,
+    #[strum(serialize = "xpath")]
+    XPath,
 }
 
 fn serialize_as_string_kebab_case<S, T>(x: &T, s: S) -> Result<S::Ok, S::Error>
@@ -333,10 +1127,11 @@ mod tests {
                 error_option: Some(ErrorOption {
                     value: ErrorOptionType::StopOnError,
                 }),
-                config: Data {
+                config: Some(Data {
                     xmlns_xc: Some("urn:ietf:params:xml:ns:netconf:base:1.0".to_string()),
                     data: " ".to_string(),
-                },
+                }),
+                url: None,
             },
         };
         let req = to_string(&model).unwrap();
@@ -363,7 +1158,7 @@ mod tests {
             xmlns: XMLNS.to_string(),
             message_id: 101,
             delete_config: DeleteConfig {
-                target: Target {
+                target: ConfigTargetType::Datastore {
                     target: DatastoreType::Startup,
                 },
             },
@@ -412,12 +1207,13 @@ urn:ietf:params:netconf:base:1.0
             xmlns: XMLNS.to_string(),
             message_id: 101,
             copy_config: CopyConfig {
-                target: Target {
+                target: ConfigTargetType::Datastore {
                     target: DatastoreType::Startup,
                 },
                 source: CopyConfigSourceType::Datastore {
                     source: DatastoreType::Running,
                 },
+                with_defaults: None,
             },
         };
         let req = to_string(&model).unwrap();
@@ -443,7 +1239,7 @@ urn:ietf:params:netconf:base:1.0
             xmlns: XMLNS.to_string(),
             message_id: 101,
             copy_config: CopyConfig {
-                target: Target {
+                target: ConfigTargetType::Datastore {
                     target: DatastoreType::Startup,
                 },
                 source: CopyConfigSourceType::Config {
@@ -452,6 +1248,7 @@ urn:ietf:params:netconf:base:1.0
                         data: " ".to_string(),
                     },
                 },
+                with_defaults: None,
             },
         };
         let req = to_string(&model).unwrap();
@@ -471,6 +1268,125 @@ urn:ietf:params:netconf:base:1.0
         assert_eq!(req, expected_req);
     }
 
+    #[test]
+    fn copy_config_url_req() {
+        let model = CopyConfigReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            copy_config: CopyConfig {
+                target: ConfigTargetType::Url {
+                    url: Url::new("file:///backup.xml?a&b"),
+                },
+                source: CopyConfigSourceType::Datastore {
+                    source: DatastoreType::Running,
+                },
+                with_defaults: None,
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<copy-config>
+<target>
+<url>file:///backup.xml?a&amp;b</url>
+</target>
+<source>
+<running/>
+</source>
+</copy-config>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
+    #[test]
+    fn partial_lock_req() {
+        let model = PartialLockReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            partial_lock: PartialLock {
+                xmlns: "urn:ietf:params:xml:ns:netconf:partial-lock:1.0".to_string(),
+                select: vec![
+                    Select {
+                        value: "/t:users".to_string(),
+                    },
+                    Select {
+                        value: "/t:groups[t:id<3]".to_string(),
+                    },
+                ],
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<partial-lock xmlns="urn:ietf:params:xml:ns:netconf:partial-lock:1.0">
+<select>/t:users</select>
+<select>/t:groups[t:id&lt;3]</select>
+</partial-lock>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
+    #[test]
+    fn get_schema_req() {
+        let model = GetSchemaReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            get_schema: GetSchema {
+                xmlns: "urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring".to_string(),
+                identifier: Identifier {
+                    value: "test".to_string(),
+                },
+                version: Some(Version {
+                    value: "2021-06-01".to_string(),
+                }),
+                format: Some(Format {
+                    value: SchemaFormat::Yang,
+                }),
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<get-schema xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring">
+<identifier>test</identifier>
+<version>2021-06-01</version>
+<format>yang</format>
+</get-schema>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
+    #[test]
+    fn validate_req() {
+        let model = ValidateReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            validate: Validate {
+                source: ValidateSourceType::Datastore {
+                    source: DatastoreType::Candidate,
+                },
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<validate>
+<source>
+<candidate/>
+</source>
+</validate>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
     #[test]
     fn commit_req() {
         let model = CommitReq {
@@ -488,6 +1404,57 @@ urn:ietf:params:netconf:base:1.0
         assert_eq!(req, expected_req);
     }
 
+    #[test]
+    fn confirmed_commit_req() {
+        let model = CommitReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            commit: Commit {
+                confirmed: Some(Confirmed {}),
+                confirm_timeout: Some(ConfirmTimeout { value: 120 }),
+                persist: Some(Persist {
+                    value: "change-42".to_string(),
+                }),
+                persist_id: None,
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<commit>
+<confirmed/>
+<confirm-timeout>120</confirm-timeout>
+<persist>change-42</persist>
+</commit>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
+    #[test]
+    fn cancel_commit_req() {
+        let model = CancelCommitReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            cancel_commit: CancelCommit {
+                persist_id: Some(PersistId {
+                    value: "change-42".to_string(),
+                }),
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<cancel-commit>
+<persist-id>change-42</persist-id>
+</cancel-commit>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
     #[test]
     fn unlock_req() {
         let model = UnlockReq {
@@ -560,10 +1527,8 @@ urn:ietf:params:netconf:base:1.0
                 source: Target {
                     target: DatastoreType::Running,
                 },
-                filter: Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: " ".to_string(),
-                }),
+                filter: Some(Filter::subtree(" ")),
+                with_defaults: Some(WithDefaultsMode::ReportAllTagged.into()),
             },
         };
         let req = to_string(&model).unwrap();
@@ -574,6 +1539,7 @@ urn:ietf:params:netconf:base:1.0
 <running/>
 </source>
 <filter type="subtree"> </filter>
+<with-defaults xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-with-defaults">report-all-tagged</with-defaults>
 </get-config>
 </rpc>
 "#
@@ -589,8 +1555,11 @@ urn:ietf:params:netconf:base:1.0
             get: Get {
                 filter: Some(Filter {
                     filter_type: FilterType::Subtree,
+                    select: None,
                     data: " ".to_string(),
+                    namespaces: BTreeMap::new(),
                 }),
+                with_defaults: None,
             },
         };
         let req = to_string(&model).unwrap();
@@ -605,6 +1574,46 @@ urn:ietf:params:netconf:base:1.0
         assert_eq!(req, expected_req);
     }
 
+    #[test]
+    fn get_xpath_req() {
+        let model = GetReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            get: Get {
+                filter: Some(Filter {
+                    data: " ".to_string(),
+                    ..Filter::xpath("/t:users/t:user[t:name='x']", BTreeMap::new())
+                }),
+                with_defaults: None,
+            },
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">
+<get>
+<filter type="xpath" select="/t:users/t:user[t:name=&apos;x&apos;]"> </filter>
+</get>
+</rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
+    #[test]
+    fn raw_rpc_req() {
+        let model = RawRpcReq {
+            xmlns: XMLNS.to_string(),
+            message_id: 101,
+            data: " ".to_string(),
+        };
+        let req = to_string(&model).unwrap();
+        let expected_req = r#"
+<rpc message-id="101" xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"> </rpc>
+"#
+        .replace("\n", "");
+        assert_eq!(req, expected_req);
+    }
+
     #[test]
     fn discard_changes_req() {
         let model = DiscardChangesReq {
@@ -0,0 +1,171 @@
+use crate::errors::NetconfClientError;
+use crate::notification::Notification;
+use serde::Deserialize;
+use std::fmt;
+
+pub const SUBSCRIBED_NOTIFICATIONS_NAMESPACE: &str =
+    "urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications";
+
+/// Identifier of a dynamic subscription assigned by the server.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubscriptionId(pub u32);
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Content of `subscription-started` and `subscription-modified`.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SubscriptionParameters {
+    pub id: SubscriptionId,
+    pub stream: Option<String>,
+    #[serde(rename = "stream-xpath-filter")]
+    pub stream_xpath_filter: Option<String>,
+    #[serde(rename = "stream-filter-name")]
+    pub stream_filter_name: Option<String>,
+    #[serde(rename = "replay-start-time")]
+    pub replay_start_time: Option<String>,
+    #[serde(rename = "stop-time")]
+    pub stop_time: Option<String>,
+    pub dscp: Option<u8>,
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SubscriptionReason {
+    pub id: SubscriptionId,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct SubscriptionIdOnly {
+    pub id: SubscriptionId,
+}
+
+/// RFC 8639 subscription state change notification.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SubscriptionStateChange {
+    Started(SubscriptionParameters),
+    Modified(SubscriptionParameters),
+    Suspended(SubscriptionReason),
+    Resumed(SubscriptionIdOnly),
+    Terminated(SubscriptionReason),
+    ReplayCompleted(SubscriptionIdOnly),
+    Completed(SubscriptionIdOnly),
+}
+
+impl SubscriptionStateChange {
+    /// Returns `None` for notifications which are not subscription state
+    /// changes.
+    pub fn from_notification(
+        notification: &Notification,
+    ) -> Result<Option<SubscriptionStateChange>, NetconfClientError> {
+        if notification.event_namespace.as_deref() != Some(SUBSCRIBED_NOTIFICATIONS_NAMESPACE) {
+            return Ok(None);
+        }
+        let event = notification.event.as_str();
+        let change = match notification.event_name.as_str() {
+            "subscription-started" => SubscriptionStateChange::Started(from_str(event)?),
+            "subscription-modified" => SubscriptionStateChange::Modified(from_str(event)?),
+            "subscription-suspended" => SubscriptionStateChange::Suspended(from_str(event)?),
+            "subscription-resumed" => SubscriptionStateChange::Resumed(from_str(event)?),
+            "subscription-terminated" => SubscriptionStateChange::Terminated(from_str(event)?),
+            "replay-completed" => SubscriptionStateChange::ReplayCompleted(from_str(event)?),
+            "subscription-completed" => SubscriptionStateChange::Completed(from_str(event)?),
+            _ => return Ok(None),
+        };
+        Ok(Some(change))
+    }
+
+    pub fn id(&self) -> SubscriptionId {
+        match self {
+            SubscriptionStateChange::Started(parameters)
+            | SubscriptionStateChange::Modified(parameters) => parameters.id,
+            SubscriptionStateChange::Suspended(reason)
+            | SubscriptionStateChange::Terminated(reason) => reason.id,
+            SubscriptionStateChange::Resumed(change)
+            | SubscriptionStateChange::ReplayCompleted(change)
+            | SubscriptionStateChange::Completed(change) => change.id,
+        }
+    }
+
+    /// Whether the subscription no longer exists on the server.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionStateChange::Terminated(_) | SubscriptionStateChange::Completed(_)
+        )
+    }
+}
+
+fn from_str<T: serde::de::DeserializeOwned>(event: &str) -> Result<T, NetconfClientError> {
+    Ok(quick_xml::de::from_str(event)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::notification;
+
+    #[test]
+    fn state_changes() {
+        let started = notification(
+            "2021-06-01T10:00:00Z",
+            r#"<subscription-started xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications">
+    <id>39</id>
+    <stream>NETCONF</stream>
+    <stream-xpath-filter xmlns:ex="http://example.com/events">/ex:foo</stream-xpath-filter>
+    <stop-time>2021-06-02T10:00:00Z</stop-time>
+    <dscp>10</dscp>
+    <encoding>encode-xml</encoding>
+</subscription-started>"#,
+        );
+        match SubscriptionStateChange::from_notification(&started).unwrap() {
+            Some(SubscriptionStateChange::Started(parameters)) => {
+                assert_eq!(parameters.id, SubscriptionId(39));
+                assert_eq!(parameters.stream.as_deref(), Some("NETCONF"));
+                assert_eq!(parameters.stream_xpath_filter.as_deref(), Some("/ex:foo"));
+                assert_eq!(parameters.dscp, Some(10));
+                assert_eq!(parameters.encoding.as_deref(), Some("encode-xml"));
+            }
+            change => panic!("Unexpected state change {:?}", change),
+        }
+
+        let terminated = notification(
+            "2021-06-01T10:00:00Z",
+            r#"<subscription-terminated xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications" xmlns:sn="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications"><id>39</id><reason>sn:filter-unavailable</reason></subscription-terminated>"#,
+        );
+        let change = SubscriptionStateChange::from_notification(&terminated)
+            .unwrap()
+            .unwrap();
+        assert_eq!(change.id(), SubscriptionId(39));
+        assert!(change.is_final());
+        assert_eq!(
+            change,
+            SubscriptionStateChange::Terminated(SubscriptionReason {
+                id: SubscriptionId(39),
+                reason: "sn:filter-unavailable".to_string(),
+            })
+        );
+
+        let resumed = notification(
+            "2021-06-01T10:00:00Z",
+            r#"<subscription-resumed xmlns="urn:ietf:params:xml:ns:yang:ietf-subscribed-notifications"><id>39</id></subscription-resumed>"#,
+        );
+        assert!(!SubscriptionStateChange::from_notification(&resumed)
+            .unwrap()
+            .unwrap()
+            .is_final());
+
+        let other = notification(
+            "2021-06-01T10:00:00Z",
+            r#"<event xmlns="http://example.com/events"/>"#,
+        );
+        assert_eq!(
+            SubscriptionStateChange::from_notification(&other).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Deserialize;
+
+pub const YANG_LIBRARY_NAMESPACE: &str = "urn:ietf:params:xml:ns:yang:ietf-yang-library";
+
+/// RFC 7895 `modules-state` container.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ModulesState {
+    #[serde(rename = "module-set-id")]
+    pub module_set_id: String,
+    #[serde(rename = "module", default)]
+    pub modules: Vec<ModuleState>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ModuleState {
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<String>,
+    #[serde(default)]
+    pub schema: Option<String>,
+    pub namespace: String,
+    #[serde(rename = "feature", default)]
+    pub features: Vec<String>,
+    #[serde(rename = "deviation", default)]
+    pub deviations: Vec<ModuleStateDeviation>,
+    #[serde(rename = "conformance-type")]
+    pub conformance_type: ConformanceType,
+    #[serde(rename = "submodule", default)]
+    pub submodules: Vec<ModuleStateSubmodule>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ModuleStateDeviation {
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ModuleStateSubmodule {
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<String>,
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ConformanceType {
+    #[serde(rename = "$value")]
+    pub value: ConformanceTypeE,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ConformanceTypeE {
+    Implement,
+    Import,
+}
+
+/// RFC 8525 `yang-library` container.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct YangLibraryContent {
+    #[serde(rename = "module-set", default)]
+    pub module_sets: Vec<ModuleSet>,
+    #[serde(rename = "schema", default)]
+    pub schemas: Vec<Schema>,
+    #[serde(rename = "datastore", default)]
+    pub datastores: Vec<Datastore>,
+    #[serde(rename = "content-id")]
+    pub content_id: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ModuleSet {
+    pub name: String,
+    #[serde(rename = "module", default)]
+    pub modules: Vec<YangModule>,
+    #[serde(rename = "import-only-module", default)]
+    pub import_only_modules: Vec<ImportOnlyModule>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct YangModule {
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<String>,
+    pub namespace: String,
+    #[serde(rename = "location", default)]
+    pub locations: Vec<String>,
+    #[serde(rename = "submodule", default)]
+    pub submodules: Vec<Submodule>,
+    #[serde(rename = "feature", default)]
+    pub features: Vec<String>,
+    #[serde(rename = "deviation", default)]
+    pub deviations: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ImportOnlyModule {
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<String>,
+    pub namespace: String,
+    #[serde(rename = "location", default)]
+    pub locations: Vec<String>,
+    #[serde(rename = "submodule", default)]
+    pub submodules: Vec<Submodule>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Submodule {
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<String>,
+    #[serde(rename = "location", default)]
+    pub locations: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Schema {
+    pub name: String,
+    #[serde(rename = "module-set", default)]
+    pub module_sets: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Datastore {
+    pub name: String,
+    pub schema: String,
+}
+
+impl ModulesState {
+    pub fn module(&self, name: &str) -> Option<&ModuleState> {
+        self.modules.iter().find(|module| module.name == name)
+    }
+}
+
+impl YangLibraryContent {
+    pub fn modules(&self) -> impl Iterator<Item = &YangModule> {
+        self.module_sets.iter().flat_map(|set| set.modules.iter())
+    }
+
+    pub fn module(&self, name: &str) -> Option<&YangModule> {
+        self.modules().find(|module| module.name == name)
+    }
+
+    /// Module sets of the schema used by the datastore, e.g. `ds:running`.
+    pub fn datastore_module_sets(&self, datastore: &str) -> Vec<&ModuleSet> {
+        let schema = self
+            .datastores
+            .iter()
+            .find(|item| item.name == datastore)
+            .and_then(|item| {
+                self.schemas
+                    .iter()
+                    .find(|schema| schema.name == item.schema)
+            });
+        match schema {
+            Some(schema) => self
+                .module_sets
+                .iter()
+                .filter(|set| schema.module_sets.contains(&set.name))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de::from_str;
+
+    #[test]
+    fn modules_state() {
+        let xml = r#"
+<modules-state xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-library">
+    <module-set-id>14</module-set-id>
+    <module>
+        <name>ietf-interfaces</name>
+        <revision>2014-05-08</revision>
+        <schema>http://example.com/ietf-interfaces.yang</schema>
+        <namespace>urn:ietf:params:xml:ns:yang:ietf-interfaces</namespace>
+        <feature>arbitrary-names</feature>
+        <feature>pre-provisioning</feature>
+        <deviation>
+            <name>vendor-dev</name>
+            <revision>2020-01-01</revision>
+        </deviation>
+        <conformance-type>implement</conformance-type>
+    </module>
+    <module>
+        <name>ietf-yang-types</name>
+        <revision>2013-07-15</revision>
+        <namespace>urn:ietf:params:xml:ns:yang:ietf-yang-types</namespace>
+        <conformance-type>import</conformance-type>
+    </module>
+</modules-state>
+"#;
+        let state: ModulesState = from_str(xml).unwrap();
+        assert_eq!(state.module_set_id, "14");
+        assert_eq!(state.modules.len(), 2);
+        let module = state.module("ietf-interfaces").unwrap();
+        assert_eq!(module.revision, Some("2014-05-08".to_string()));
+        assert_eq!(module.features, vec!["arbitrary-names", "pre-provisioning"]);
+        assert_eq!(
+            module.deviations,
+            vec![ModuleStateDeviation {
+                name: "vendor-dev".to_string(),
+                revision: Some("2020-01-01".to_string()),
+            }]
+        );
+        assert_eq!(module.conformance_type.value, ConformanceTypeE::Implement);
+        assert_eq!(
+            state
+                .module("ietf-yang-types")
+                .unwrap()
+                .conformance_type
+                .value,
+            ConformanceTypeE::Import
+        );
+    }
+
+    #[test]
+    fn yang_library() {
+        let xml = r#"
+<yang-library xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-library" xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">
+    <module-set>
+        <name>complete</name>
+        <module>
+            <name>ietf-interfaces</name>
+            <revision>2018-02-20</revision>
+            <namespace>urn:ietf:params:xml:ns:yang:ietf-interfaces</namespace>
+            <feature>if-mib</feature>
+            <deviation>vendor-dev</deviation>
+        </module>
+        <import-only-module>
+            <name>ietf-yang-types</name>
+            <revision>2013-07-15</revision>
+            <namespace>urn:ietf:params:xml:ns:yang:ietf-yang-types</namespace>
+        </import-only-module>
+    </module-set>
+    <schema>
+        <name>complete</name>
+        <module-set>complete</module-set>
+    </schema>
+    <datastore>
+        <name>ds:running</name>
+        <schema>complete</schema>
+    </datastore>
+    <datastore>
+        <name>ds:operational</name>
+        <schema>complete</schema>
+    </datastore>
+    <content-id>75</content-id>
+</yang-library>
+"#;
+        let library: YangLibraryContent = from_str(xml).unwrap();
+        assert_eq!(library.content_id, "75");
+        let module = library.module("ietf-interfaces").unwrap();
+        assert_eq!(module.features, vec!["if-mib"]);
+        assert_eq!(module.deviations, vec!["vendor-dev"]);
+        assert_eq!(
+            library.module_sets[0].import_only_modules[0].name,
+            "ietf-yang-types"
+        );
+        assert_eq!(library.datastores.len(), 2);
+        let sets = library.datastore_module_sets("ds:running");
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].name, "complete");
+    }
+}
@@ -0,0 +1,270 @@
+use crate::errors::NetconfClientError;
+use crate::models::subscribed_notifications::SubscriptionId;
+use crate::notification::Notification;
+use crate::xml::Element;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+pub const YANG_PUSH_NAMESPACE: &str = "urn:ietf:params:xml:ns:yang:ietf-yang-push";
+
+/// RFC 8641 `push-update` with the full contents selected by a periodic
+/// subscription (or the initial sync of an on-change subscription).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushUpdate {
+    pub id: SubscriptionId,
+    pub event_time: String,
+    pub datastore_contents: Vec<Element>,
+    pub incomplete_update: bool,
+}
+
+/// RFC 8641 `push-change-update` carrying a YANG Patch (RFC 8072).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushChangeUpdate {
+    pub id: SubscriptionId,
+    pub event_time: String,
+    pub patch_id: String,
+    pub comment: Option<String>,
+    pub edits: Vec<YangPatchEdit>,
+    pub incomplete_update: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YangPatchEdit {
+    pub edit_id: String,
+    pub operation: YangPatchOperation,
+    /// Data resource identifier relative to the subscription, e.g.
+    /// `/ex:interfaces/ex:interface[ex:name='eth0']`.
+    pub target: String,
+    /// Namespace declarations in scope of `target`.
+    pub namespaces: BTreeMap<String, String>,
+    pub point: Option<String>,
+    pub r#where: Option<String>,
+    pub value: Vec<Element>,
+}
+
+#[derive(strum_macros::Display, strum_macros::EnumString, Debug, Clone, Copy, PartialEq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum YangPatchOperation {
+    Create,
+    Delete,
+    Insert,
+    Merge,
+    Move,
+    Replace,
+    Remove,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum YangPushUpdate {
+    Update(PushUpdate),
+    ChangeUpdate(PushChangeUpdate),
+}
+
+impl YangPushUpdate {
+    /// Returns `None` for notifications which are not YANG-Push updates.
+    pub fn from_notification(
+        notification: &Notification,
+    ) -> Result<Option<YangPushUpdate>, NetconfClientError> {
+        if notification.event_namespace.as_deref() != Some(YANG_PUSH_NAMESPACE) {
+            return Ok(None);
+        }
+        let root = Element::parse(&notification.raw)?;
+        let mut scope = root.declarations.clone();
+        let event = match root.child(&notification.event_name) {
+            Some(event) => event,
+            None => return Ok(None),
+        };
+        scope.extend(event.declarations.clone());
+        let id = SubscriptionId(parse_text(event, "id")?);
+        let event_time = notification.event_time.clone();
+        let incomplete_update = event.child("incomplete-update").is_some();
+        let update = match event.name.as_str() {
+            "push-update" => YangPushUpdate::Update(PushUpdate {
+                id,
+                event_time,
+                datastore_contents: event
+                    .child("datastore-contents")
+                    .map(detached_children)
+                    .unwrap_or_default(),
+                incomplete_update,
+            }),
+            "push-change-update" => {
+                let changes = required(event, "datastore-changes")?;
+                scope.extend(changes.declarations.clone());
+                let patch = required(changes, "yang-patch")?;
+                scope.extend(patch.declarations.clone());
+                YangPushUpdate::ChangeUpdate(PushChangeUpdate {
+                    id,
+                    event_time,
+                    patch_id: required(patch, "patch-id")?.text.clone(),
+                    comment: patch.child_text("comment").map(str::to_string),
+                    edits: patch
+                        .children_named("edit")
+                        .map(|edit| parse_edit(edit, &scope))
+                        .collect::<Result<_, _>>()?,
+                    incomplete_update,
+                })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(update))
+    }
+
+    pub fn id(&self) -> SubscriptionId {
+        match self {
+            YangPushUpdate::Update(update) => update.id,
+            YangPushUpdate::ChangeUpdate(update) => update.id,
+        }
+    }
+}
+
+/// Decodes the YANG-Push updates among received notifications, e.g.
+/// `push_updates(client.notifications())`. Other notifications are skipped.
+pub fn push_updates<I>(
+    notifications: I,
+) -> impl Iterator<Item = Result<YangPushUpdate, NetconfClientError>>
+where
+    I: IntoIterator<Item = Result<Notification, NetconfClientError>>,
+{
+    notifications.into_iter().filter_map(|notification| {
+        notification
+            .and_then(|notification| YangPushUpdate::from_notification(&notification))
+            .transpose()
+    })
+}
+
+fn parse_edit(
+    edit: &Element,
+    scope: &BTreeMap<String, String>,
+) -> Result<YangPatchEdit, NetconfClientError> {
+    let mut namespaces = scope.clone();
+    namespaces.extend(edit.declarations.clone());
+    let target = required(edit, "target")?;
+    namespaces.extend(target.declarations.clone());
+    let operation = required(edit, "operation")?.text.as_str();
+    Ok(YangPatchEdit {
+        edit_id: required(edit, "edit-id")?.text.clone(),
+        operation: YangPatchOperation::from_str(operation)
+            .map_err(|_| invalid(&format!("unknown yang-patch operation {}", operation)))?,
+        target: target.text.clone(),
+        namespaces,
+        point: edit.child_text("point").map(str::to_string),
+        r#where: edit.child_text("where").map(str::to_string),
+        value: edit
+            .child("value")
+            .map(detached_children)
+            .unwrap_or_default(),
+    })
+}
+
+fn detached_children(element: &Element) -> Vec<Element> {
+    element.children.iter().map(Element::detached).collect()
+}
+
+fn required<'a>(element: &'a Element, name: &str) -> Result<&'a Element, NetconfClientError> {
+    element
+        .child(name)
+        .ok_or_else(|| invalid(&format!("missing {} in {}", name, element.name)))
+}
+
+fn parse_text<T: FromStr>(element: &Element, name: &str) -> Result<T, NetconfClientError> {
+    let text = &required(element, name)?.text;
+    text.parse()
+        .map_err(|_| invalid(&format!("invalid {} {}", name, text)))
+}
+
+fn invalid(message: &str) -> NetconfClientError {
+    NetconfClientError::DeserializeError(quick_xml::DeError::Custom(message.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_update() {
+        let notification = Notification::parse(
+            r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0">
+    <eventTime>2021-06-01T10:00:00.00Z</eventTime>
+    <push-update xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-push">
+        <id>1011</id>
+        <datastore-contents>
+            <interfaces xmlns="http://example.com/interfaces">
+                <interface><name>eth0</name><oper-status>up</oper-status></interface>
+            </interfaces>
+        </datastore-contents>
+    </push-update>
+</notification>"#,
+        );
+        match YangPushUpdate::from_notification(&notification).unwrap() {
+            Some(YangPushUpdate::Update(update)) => {
+                assert_eq!(update.id, SubscriptionId(1011));
+                assert_eq!(update.event_time, "2021-06-01T10:00:00.00Z");
+                assert!(!update.incomplete_update);
+                assert_eq!(update.datastore_contents.len(), 1);
+                assert_eq!(
+                    update.datastore_contents[0].to_xml(),
+                    r#"<interfaces xmlns="http://example.com/interfaces"><interface><name>eth0</name><oper-status>up</oper-status></interface></interfaces>"#
+                );
+            }
+            update => panic!("Unexpected update {:?}", update),
+        }
+    }
+
+    #[test]
+    fn push_change_update() {
+        let notification = Notification::parse(
+            r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0" xmlns:if="http://example.com/interfaces">
+    <eventTime>2021-06-01T10:00:00Z</eventTime>
+    <push-change-update xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-push">
+        <id>89</id>
+        <datastore-changes>
+            <yang-patch>
+                <patch-id>0</patch-id>
+                <edit>
+                    <edit-id>edit1</edit-id>
+                    <operation>replace</operation>
+                    <target>/if:interfaces/if:interface[if:name='eth0']/if:oper-status</target>
+                    <value><if:oper-status>down</if:oper-status></value>
+                </edit>
+                <edit>
+                    <edit-id>edit2</edit-id>
+                    <operation>delete</operation>
+                    <target>/if:interfaces/if:interface[if:name='eth1']</target>
+                </edit>
+            </yang-patch>
+        </datastore-changes>
+        <incomplete-update/>
+    </push-change-update>
+</notification>"#,
+        );
+        let update = match YangPushUpdate::from_notification(&notification).unwrap() {
+            Some(YangPushUpdate::ChangeUpdate(update)) => update,
+            update => panic!("Unexpected update {:?}", update),
+        };
+        assert_eq!(update.id, SubscriptionId(89));
+        assert_eq!(update.patch_id, "0");
+        assert!(update.incomplete_update);
+        assert_eq!(update.edits.len(), 2);
+        let edit = &update.edits[0];
+        assert_eq!(edit.operation, YangPatchOperation::Replace);
+        assert_eq!(
+            edit.namespaces.get("if").map(String::as_str),
+            Some("http://example.com/interfaces")
+        );
+        assert_eq!(
+            edit.value[0].to_xml(),
+            r#"<if:oper-status xmlns:if="http://example.com/interfaces">down</if:oper-status>"#
+        );
+        assert_eq!(update.edits[1].operation, YangPatchOperation::Delete);
+        assert!(update.edits[1].value.is_empty());
+
+        let other = Notification::parse(
+            r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0"><eventTime>2021-06-01T10:00:00Z</eventTime><event xmlns="http://example.com/events"/></notification>"#,
+        );
+        let updates: Vec<_> = push_updates(vec![Ok(other), Ok(notification)])
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(updates.len(), 1);
+    }
+}
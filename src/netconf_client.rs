@@ -1,15 +1,38 @@
-use crate::consts;
+use crate::capabilities::{Capabilities, CapabilityPolicy, WithDefaultsMode, YangLibraryVersion};
 use crate::errors::NetconfClientError;
-use crate::errors::NetconfClientError::{NetconfError, NetconfResponseIdError, SSHClientError};
+use crate::errors::NetconfClientError::SSHClientError;
+use crate::framing::{self, MessageDecoder};
+use crate::messages;
+use crate::models::netconf_monitoring::{
+    DatastoreState, NetconfState, SchemaState, SessionState, Statistics,
+};
+use crate::models::subscribed_notifications::{SubscriptionId, SubscriptionStateChange};
+use crate::models::yang_library::{ModulesState, YangLibraryContent};
 use crate::models::{replies::*, requests::*};
+use crate::notification::Notification;
+use crate::observer::{Direction, MessageObserver, Redactor, Tracer};
+use crate::schema_cache::{self, SchemaCache, SchemaId};
+use crate::session::{ClientCapabilities, Framing, Session, YangLibraryCache};
 use crate::ssh_client::SSHClient;
-use quick_xml::se::to_string;
-use std::io::{Read, Write};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 pub struct NetconfClient {
     ssh_client: SSHClient,
     id: u32,
-    session_id: Option<u32>,
+    capabilities: ClientCapabilities,
+    session: Option<Session>,
+    capability_policy: CapabilityPolicy,
+    yang_library_cache: YangLibraryCache,
+    decoder: MessageDecoder,
+    tracer: Option<Tracer>,
+    notifications: VecDeque<Notification>,
+    subscription_active: bool,
+    dynamic_subscriptions: BTreeSet<SubscriptionId>,
 }
 
 impl NetconfClient {
@@ -17,35 +40,81 @@ impl NetconfClient {
         NetconfClient {
             ssh_client: SSHClient::create(host, port, user, password),
             id: 0,
-            session_id: None,
+            capabilities: ClientCapabilities::default(),
+            session: None,
+            capability_policy: CapabilityPolicy::default(),
+            yang_library_cache: YangLibraryCache::default(),
+            decoder: MessageDecoder::default(),
+            tracer: None,
+            notifications: VecDeque::new(),
+            subscription_active: false,
+            dynamic_subscriptions: BTreeSet::new(),
         }
     }
 
     pub fn get_session_id(&self) -> Option<u32> {
-        self.session_id
+        self.session.as_ref().map(|session| session.session_id)
+    }
+
+    pub fn get_session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    pub fn server_capabilities(&self) -> Option<&Capabilities> {
+        self.session
+            .as_ref()
+            .map(|session| &session.server_capabilities)
+    }
+
+    pub fn set_capability_policy(&mut self, policy: CapabilityPolicy) {
+        self.capability_policy = policy;
+    }
+
+    pub fn set_client_capabilities(&mut self, capabilities: ClientCapabilities) {
+        self.capabilities = capabilities;
+    }
+
+    pub fn set_observer(&mut self, observer: Arc<dyn MessageObserver>, redactor: Redactor) {
+        self.tracer = Some(Tracer::new(observer, redactor));
     }
 
     pub fn connect(&mut self) -> Result<HelloServer, NetconfClientError> {
         self.ssh_client.connect()?;
-        let reply: HelloServer = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        if reply.is_ok() {
-            self.session_id = Some(reply.session_id);
-        }
-        NetconfClient::make_return(reply)
+        // The hello exchange is always end-of-message framed, whatever the
+        // previous session negotiated.
+        self.session = None;
+        self.decoder = MessageDecoder::default();
+        self.yang_library_cache = YangLibraryCache::default();
+        self.notifications.clear();
+        self.subscription_active = false;
+        self.dynamic_subscriptions.clear();
+        let reply = messages::parse_hello(&self.get_reply()?)?;
+        let capabilities = self.capabilities.capabilities();
+        self.send(&messages::hello(&capabilities))?;
+        let session = Session::negotiate(capabilities, &reply);
+        self.decoder.set_framing(session.framing);
+        self.session = Some(session);
+        Ok(reply)
     }
 
     fn get_reply(&mut self) -> Result<String, NetconfClientError> {
-        let mut result = String::new();
+        let message = self.read_message()?;
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(Direction::Inbound, self.get_session_id(), &message);
+        }
+        Ok(message)
+    }
+
+    fn read_message(&mut self) -> Result<String, NetconfClientError> {
         loop {
-            let mut buffer = [1u8; 256];
-            let bytes_read = self.ssh_client.read(&mut buffer[..])?;
-            let s = String::from_utf8_lossy(&buffer[..bytes_read]);
-            result.push_str(&s);
-            if result.ends_with(consts::MESSAGE_SEPARATOR) {
-                break;
+            if let Some(message) = self.decoder.next_message() {
+                return Ok(message);
             }
-            if result.ends_with("##") {
-                break;
+            let mut buffer = [0u8; 256];
+            let bytes_read = self.ssh_client.read(&mut buffer[..])?;
+            self.decoder.push(&buffer[..bytes_read]);
+            if let Some(message) = self.decoder.next_message() {
+                return Ok(message);
             }
             if bytes_read == 0 || self.ssh_client.eof()? {
                 return Err(SSHClientError {
@@ -53,118 +122,285 @@ impl NetconfClient {
                 });
             }
         }
-        Ok(result)
     }
 
     fn send(&mut self, data: &str) -> Result<(), NetconfClientError> {
-        self.ssh_client.write_all(data.as_bytes())?;
+        if let Some(tracer) = &self.tracer {
+            tracer.trace(Direction::Outbound, self.get_session_id(), data);
+        }
+        self.ssh_client
+            .write_all(framing::encode(data, self.framing()).as_bytes())?;
         Ok(())
     }
 
-    pub fn send_hello(&mut self) -> Result<(), NetconfClientError> {
-        let req = HelloClient {
-            xmlns: consts::XMLNS.to_string(),
-            capabilities: CapabilitiesClient {
-                capabilities: vec![Capability {
-                    capability: "urn:ietf:params:netconf:base:1.0".to_string(),
-                }],
-            },
-        };
-        let cmd = to_string(&req).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)
+    fn check_capabilities<F>(&self, check: F) -> Result<(), NetconfClientError>
+    where
+        F: FnOnce(&Capabilities) -> Result<(), NetconfClientError>,
+    {
+        match (&self.session, self.capability_policy) {
+            (Some(session), CapabilityPolicy::Strict) => check(&session.server_capabilities),
+            _ => Ok(()),
+        }
+    }
+
+    fn declare_module_prefixes(&self, mut filter: Option<Filter>) -> Option<Filter> {
+        if let (Some(filter), Some(capabilities)) = (filter.as_mut(), self.server_capabilities()) {
+            capabilities.declare_module_prefixes(filter);
+        }
+        filter
+    }
+
+    fn framing(&self) -> Framing {
+        self.session
+            .as_ref()
+            .map_or(Framing::EndOfMessage, |session| session.framing)
+    }
+
+    fn request(&mut self, data: &str) -> Result<String, NetconfClientError> {
+        if self.subscription_active {
+            let operation = messages::rpc_operation(data).unwrap_or_default();
+            self.check_capabilities(|capabilities| capabilities.check_interleave(&operation))?;
+        }
+        self.send(data)?;
+        loop {
+            let reply = self.get_reply()?;
+            if messages::is_notification(&reply) {
+                self.receive_notification(&reply);
+            } else {
+                return Ok(reply);
+            }
+        }
+    }
+
+    fn receive_notification(&mut self, message: &str) {
+        let notification = Notification::parse(message);
+        if notification.is_notification_complete() {
+            self.subscription_active = false;
+        }
+        if let Ok(Some(change)) = SubscriptionStateChange::from_notification(&notification) {
+            if change.is_final() {
+                self.dynamic_subscriptions.remove(&change.id());
+            }
+        }
+        self.notifications.push_back(notification);
+    }
+
+    pub fn subscription_active(&self) -> bool {
+        self.subscription_active
+    }
+
+    /// Dynamic subscriptions established on this session which were not
+    /// deleted or terminated yet.
+    pub fn dynamic_subscriptions(&self) -> &BTreeSet<SubscriptionId> {
+        &self.dynamic_subscriptions
+    }
+
+    pub fn create_subscription(
+        &mut self,
+        stream: Option<&str>,
+        filter: Option<NotificationFilter>,
+        start_time: Option<&str>,
+        stop_time: Option<&str>,
+    ) -> Result<CreateSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_create_subscription(&filter))?;
+        self.id += 1;
+        let reply = self.request(&messages::create_subscription(
+            self.id, stream, filter, start_time, stop_time,
+        ))?;
+        let reply: CreateSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        self.subscription_active = true;
+        Ok(reply)
+    }
+
+    pub fn establish_subscription(
+        &mut self,
+        subscription: StreamSubscription,
+    ) -> Result<EstablishSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities
+                .check_subscribed_notifications(&subscription.filter, "establish-subscription")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::establish_subscription(self.id, subscription))?;
+        let reply: EstablishSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        if let Some(id) = reply.id {
+            self.dynamic_subscriptions.insert(id);
+        }
+        Ok(reply)
+    }
+
+    /// Establishes a YANG-Push subscription. Updates are received as
+    /// notifications which can be decoded with
+    /// [`YangPushUpdate`](crate::models::yang_push::YangPushUpdate).
+    pub fn establish_datastore_subscription(
+        &mut self,
+        subscription: DatastoreSubscription,
+    ) -> Result<EstablishSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_yang_push(&subscription))?;
+        self.id += 1;
+        let reply = self.request(&messages::establish_datastore_subscription(
+            self.id,
+            subscription,
+        ))?;
+        let reply: EstablishSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        if let Some(id) = reply.id {
+            self.dynamic_subscriptions.insert(id);
+        }
+        Ok(reply)
+    }
+
+    pub fn modify_subscription(
+        &mut self,
+        id: SubscriptionId,
+        filter: Option<NotificationFilter>,
+        stop_time: Option<&str>,
+    ) -> Result<ModifySubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&filter, "modify-subscription")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::modify_subscription(
+            self.id, id, filter, stop_time,
+        ))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn delete_subscription(
+        &mut self,
+        id: SubscriptionId,
+    ) -> Result<DeleteSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&None, "delete-subscription")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::delete_subscription(self.id, id))?;
+        let reply: DeleteSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        self.dynamic_subscriptions.remove(&id);
+        Ok(reply)
+    }
+
+    pub fn kill_subscription(
+        &mut self,
+        id: SubscriptionId,
+    ) -> Result<KillSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&None, "kill-subscription")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::kill_subscription(self.id, id))?;
+        let reply: KillSubscriptionRsp = messages::parse_reply(&reply, self.id)?;
+        self.dynamic_subscriptions.remove(&id);
+        Ok(reply)
+    }
+
+    /// Returns the next notification, waiting for one while a subscription
+    /// is active. Returns `None` once the subscription has ended and all
+    /// received notifications were consumed. Fails with `TimedOut` when
+    /// nothing arrived within the SSH timeout, the subscription stays usable.
+    pub fn next_notification(&mut self) -> Result<Option<Notification>, NetconfClientError> {
+        loop {
+            if let Some(notification) = self.notifications.pop_front() {
+                return Ok(Some(notification));
+            }
+            if !self.subscription_active && self.dynamic_subscriptions.is_empty() {
+                return Ok(None);
+            }
+            let message = self.get_reply()?;
+            if messages::is_notification(&message) {
+                self.receive_notification(&message);
+            }
+        }
+    }
+
+    pub fn notifications(&mut self) -> Notifications<'_> {
+        Notifications {
+            client: self,
+            failed: false,
+        }
     }
 
     pub fn kill_session(&mut self, session_id: u32) -> Result<KillSessionRsp, NetconfClientError> {
         self.id += 1;
-        let req = KillSessionReq {
-            message_id: self.id,
-            xmlns: consts::XMLNS.to_string(),
-            kill_session: KillSession {
-                session_id: SessionId { value: session_id },
-            },
-        };
-        let cmd = to_string(&req).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: KillSessionRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::kill_session(self.id, session_id))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn close_session(&mut self) -> Result<CloseSessionRsp, NetconfClientError> {
         self.id += 1;
-        let req = CloseSessionReq {
-            message_id: self.id,
-            xmlns: consts::XMLNS.to_string(),
-            close_session: Default::default(),
-        };
-        let cmd = to_string(&req).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: CloseSessionRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        if reply.is_ok() {
-            self.session_id = None;
-            self.ssh_client.disconnect()?;
-        }
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::close_session(self.id))?;
+        let reply: CloseSessionRsp = messages::parse_reply(&reply, self.id)?;
+        self.session = None;
+        self.ssh_client.disconnect()?;
+        Ok(reply)
     }
 
     pub fn get_config(
         &mut self,
         source: DatastoreType,
         filter: Option<Filter>,
+        with_defaults: Option<WithDefaultsMode>,
     ) -> Result<GetConfigRsp, NetconfClientError> {
+        let filter = self.declare_module_prefixes(filter);
+        self.check_capabilities(|capabilities| {
+            capabilities.check_datastore(&source, "get-config")?;
+            capabilities.check_filter(&filter, "get-config")?;
+            capabilities.check_with_defaults(with_defaults, "get-config")
+        })?;
         self.id += 1;
-        let mut filter_copy = filter.clone();
-        let filter_exists = filter.is_some();
-        if filter_exists {
-            filter_copy.as_mut().unwrap().data = " ".to_string();
-        }
-        let req = GetConfigReq {
-            message_id: self.id,
-            xmlns: consts::XMLNS.to_string(),
-            get_config: GetConfig {
-                source: Target { target: source },
-                filter: filter_copy,
-            },
-        };
-        let mut cmd = to_string(&req).unwrap() + consts::MESSAGE_SEPARATOR;
-        if filter_exists {
-            cmd.insert_str(cmd.rfind(" </filter>").unwrap(), &filter.unwrap().data);
-        }
-        self.send(&cmd)?;
-        let raw_rsp = self.get_reply()?;
-        let mut deserialized_rsp = quick_xml::de::from_str::<GetConfigRsp>(&raw_rsp).unwrap();
-        deserialized_rsp.data = Some(NetconfClient::get_data(&raw_rsp).unwrap_or("").to_string());
-        self.check_response_message_id(&deserialized_rsp)?;
-        NetconfClient::make_return(deserialized_rsp)
+        let reply = self.request(&messages::get_config(
+            self.id,
+            source,
+            filter,
+            with_defaults,
+        ))?;
+        messages::parse_get_config_reply(&reply, self.id, with_defaults)
     }
 
-    pub fn get(&mut self, filter: Option<Filter>) -> Result<GetRsp, NetconfClientError> {
+    pub fn get(
+        &mut self,
+        filter: Option<Filter>,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<GetRsp, NetconfClientError> {
+        let filter = self.declare_module_prefixes(filter);
+        self.check_capabilities(|capabilities| {
+            capabilities.check_filter(&filter, "get")?;
+            capabilities.check_with_defaults(with_defaults, "get")
+        })?;
         self.id += 1;
-        let filter_exists = filter.is_some();
-        let mut filter_copy = filter.clone();
-        if filter_exists {
-            filter_copy.as_mut().unwrap().data = " ".to_string();
-        }
-        let req = GetReq {
-            message_id: self.id,
-            xmlns: consts::XMLNS.to_string(),
-            get: Get {
-                filter: filter_copy,
-            },
-        };
+        let reply = self.request(&messages::get(self.id, filter, with_defaults))?;
+        messages::parse_get_reply(&reply, self.id, with_defaults)
+    }
 
-        // hack
-        let mut cmd = to_string(&req).unwrap() + consts::MESSAGE_SEPARATOR;
-        if filter_exists {
-            cmd.insert_str(cmd.rfind(" </filter>").unwrap(), &filter.unwrap().data);
-        }
-        self.send(&cmd)?;
-        let raw_rsp = self.get_reply()?;
-        let mut deserialized_rsp = quick_xml::de::from_str::<GetRsp>(&raw_rsp).unwrap();
-        deserialized_rsp.data = Some(NetconfClient::get_data(&raw_rsp).unwrap().to_string());
-        self.check_response_message_id(&deserialized_rsp)?;
-        NetconfClient::make_return(deserialized_rsp)
+    /// RFC 8526 `<get-data>`, which can also read the `operational` and
+    /// `intended` datastores.
+    pub fn nmda_get_data(
+        &mut self,
+        datastore: Datastore,
+        mut options: GetDataOptions,
+    ) -> Result<GetDataRsp, NetconfClientError> {
+        options.filter = self.declare_module_prefixes(options.filter);
+        self.check_capabilities(|capabilities| capabilities.check_get_data(&options))?;
+        let with_defaults = options.with_defaults;
+        self.id += 1;
+        let reply = self.request(&messages::nmda_get_data(self.id, datastore, options))?;
+        messages::parse_get_data_reply(&reply, self.id, with_defaults)
+    }
+
+    pub fn edit_data(
+        &mut self,
+        datastore: Datastore,
+        data: String,
+        default_operation: Option<DefaultOperationType>,
+    ) -> Result<EditDataRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_edit_data())?;
+        self.id += 1;
+        let reply = self.request(&messages::nmda_edit_data(
+            self.id,
+            datastore,
+            data,
+            default_operation,
+        ))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn edit_config(
@@ -175,185 +411,360 @@ impl NetconfClient {
         test_option: Option<TestOptionType>,
         error_option: Option<ErrorOptionType>,
     ) -> Result<EditConfigRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_edit_config(&source, &test_option, &error_option)
+        })?;
         self.id += 1;
-        let mut req = EditConfigReq {
-            message_id: self.id,
-            xmlns: consts::XMLNS.to_string(),
-            edit_config: EditConfig {
-                target: Target { target: source },
-                default_operation: None,
-                test_option: None,
-                error_option: None,
-                config: Data {
-                    xmlns_xc: Some("urn:ietf:params:xml:ns:netconf:base:1.0".to_string()),
-                    data: " ".to_string(),
-                },
-            },
-        };
-        if let Some(default_operation) = default_operation {
-            req.edit_config.default_operation = Some(DefaultOperation {
-                value: default_operation,
-            })
-        }
-        if let Some(test_option) = test_option {
-            req.edit_config.test_option = Some(TestOption { value: test_option })
-        }
-        if let Some(error_option) = error_option {
-            req.edit_config.error_option = Some(ErrorOption {
-                value: error_option,
-            })
-        }
-        let mut cmd = to_string(&req).unwrap() + consts::MESSAGE_SEPARATOR;
-        // hack
-        cmd.insert_str(cmd.rfind(" </config>").unwrap(), &data);
-        self.send(&cmd)?;
-        let reply: EditConfigRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::edit_config(
+            self.id,
+            source,
+            data,
+            default_operation,
+            test_option,
+            error_option,
+        ))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    /// `<edit-config>` with the configuration loaded by the server from `url`.
+    pub fn edit_config_url(
+        &mut self,
+        source: DatastoreType,
+        url: &str,
+        default_operation: Option<DefaultOperationType>,
+        test_option: Option<TestOptionType>,
+        error_option: Option<ErrorOptionType>,
+    ) -> Result<EditConfigRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_edit_config(&source, &test_option, &error_option)?;
+            capabilities.check_url(url, "edit-config")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::edit_config_url(
+            self.id,
+            source,
+            url,
+            default_operation,
+            test_option,
+            error_option,
+        ))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn lock(&mut self, target: DatastoreType) -> Result<LockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_datastore(&target, "lock"))?;
         self.id += 1;
-        let model = LockReq {
-            xmlns: consts::XMLNS.to_string(),
-            message_id: self.id,
-            lock: Lock {
-                target: Target { target },
-            },
-        };
-        let lock_cmd = to_string(&model).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&lock_cmd)?;
-        let reply: LockRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::lock(self.id, target))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn unlock(&mut self, target: DatastoreType) -> Result<UnlockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_datastore(&target, "unlock"))?;
         self.id += 1;
-        let model = UnlockReq {
-            xmlns: consts::XMLNS.to_string(),
-            message_id: self.id,
-            unlock: Unlock {
-                target: Target { target },
-            },
-        };
-        let cmd = to_string(&model).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: UnlockRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::unlock(self.id, target))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn partial_lock(
+        &mut self,
+        select: Vec<String>,
+        namespaces: &BTreeMap<String, String>,
+    ) -> Result<PartialLockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_partial_lock("partial-lock"))?;
+        self.id += 1;
+        let reply = self.request(&messages::partial_lock(self.id, select, namespaces))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn partial_unlock(&mut self, lock_id: u32) -> Result<PartialUnlockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_partial_lock("partial-unlock"))?;
+        self.id += 1;
+        let reply = self.request(&messages::partial_unlock(self.id, lock_id))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn delete_config(
         &mut self,
-        target: DatastoreType,
+        target: impl Into<ConfigTargetType>,
     ) -> Result<DeleteConfigRsp, NetconfClientError> {
+        let target = target.into();
+        self.check_capabilities(|capabilities| capabilities.check_delete_config(&target))?;
         self.id += 1;
-        let model = DeleteConfigReq {
-            xmlns: consts::XMLNS.to_string(),
-            message_id: self.id,
-            delete_config: DeleteConfig {
-                target: Target { target },
-            },
-        };
-        let cmd = to_string(&model).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: DeleteConfigRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::delete_config(self.id, target))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn discard_changes(&mut self) -> Result<DiscardChangesRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_candidate("discard-changes"))?;
         self.id += 1;
-        let model = DiscardChangesReq {
-            xmlns: consts::XMLNS.to_string(),
-            message_id: self.id,
-            discard_changes: Default::default(),
-        };
-        let cmd = to_string(&model).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: DiscardChangesRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::discard_changes(self.id))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn commit(&mut self) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_candidate("commit"))?;
         self.id += 1;
-        let model = CommitReq {
-            xmlns: consts::XMLNS.to_string(),
-            message_id: self.id,
-            commit: Default::default(),
-        };
-        let cmd = to_string(&model).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: CommitRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        let reply = self.request(&messages::commit(self.id, Commit::default()))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn confirmed_commit(
+        &mut self,
+        confirm_timeout: Option<u32>,
+        persist: Option<String>,
+    ) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_confirmed_commit("commit"))?;
+        self.id += 1;
+        let reply = self.request(&messages::confirmed_commit(
+            self.id,
+            confirm_timeout,
+            persist,
+        ))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn confirm_commit(
+        &mut self,
+        persist_id: Option<String>,
+    ) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_confirmed_commit("commit"))?;
+        self.id += 1;
+        let reply = self.request(&messages::confirm_commit(self.id, persist_id))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn cancel_commit(
+        &mut self,
+        persist_id: Option<String>,
+    ) -> Result<CancelCommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_confirmed_commit("cancel-commit")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::cancel_commit(self.id, persist_id))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn validate(
+        &mut self,
+        source: ValidateSourceType,
+    ) -> Result<ValidateRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_validate(&source))?;
+        self.id += 1;
+        let reply = self.request(&messages::validate(self.id, source))?;
+        messages::parse_reply(&reply, self.id)
     }
 
     pub fn copy_config(
         &mut self,
-        target: DatastoreType,
+        target: impl Into<ConfigTargetType>,
         source: CopyConfigSourceType,
+        with_defaults: Option<WithDefaultsMode>,
     ) -> Result<CopyConfigRsp, NetconfClientError> {
-        // TODO
-        self.id += 1;
-        let model = CopyConfigReq {
-            xmlns: consts::XMLNS.to_string(),
-            message_id: self.id,
-            copy_config: CopyConfig {
-                target: Target { target },
-                source,
-            },
+        let target = target.into();
+        self.check_capabilities(|capabilities| {
+            capabilities.check_copy_config(&target, &source)?;
+            capabilities.check_with_defaults(with_defaults, "copy-config")
+        })?;
+        self.id += 1;
+        let reply = self.request(&messages::copy_config(
+            self.id,
+            target,
+            source,
+            with_defaults,
+        ))?;
+        messages::parse_reply(&reply, self.id)
+    }
+
+    pub fn get_netconf_state(&mut self) -> Result<NetconfState, NetconfClientError> {
+        self.netconf_state(None)
+    }
+
+    pub fn get_sessions(&mut self) -> Result<Vec<SessionState>, NetconfClientError> {
+        let state = self.netconf_state(Some("sessions"))?;
+        Ok(state.sessions.unwrap_or_default().sessions)
+    }
+
+    pub fn get_datastores(&mut self) -> Result<Vec<DatastoreState>, NetconfClientError> {
+        let state = self.netconf_state(Some("datastores"))?;
+        Ok(state.datastores.unwrap_or_default().datastores)
+    }
+
+    pub fn get_statistics(&mut self) -> Result<Statistics, NetconfClientError> {
+        let state = self.netconf_state(Some("statistics"))?;
+        Ok(state.statistics.unwrap_or_default())
+    }
+
+    pub fn get_schema_list(&mut self) -> Result<Vec<SchemaState>, NetconfClientError> {
+        let state = self.netconf_state(Some("schemas"))?;
+        Ok(state.schemas.unwrap_or_default().schemas)
+    }
+
+    fn netconf_state(
+        &mut self,
+        container: Option<&str>,
+    ) -> Result<NetconfState, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_monitoring("get"))?;
+        self.id += 1;
+        let reply = self.request(&messages::get_netconf_state(self.id, container))?;
+        messages::parse_data_reply(&reply, self.id)
+    }
+
+    pub fn get_schema(
+        &mut self,
+        identifier: &str,
+        version: Option<&str>,
+        format: Option<SchemaFormat>,
+    ) -> Result<GetSchemaRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_monitoring("get-schema"))?;
+        self.id += 1;
+        let reply = self.request(&messages::get_schema(self.id, identifier, version, format))?;
+        messages::parse_get_schema_reply(&reply, self.id)
+    }
+
+    /// Downloads schemas of all modules listed in the hello and in the YANG
+    /// library which are not in the cache yet. Schemas whose identifier or
+    /// revision is not a safe file name are skipped. Returns paths of new
+    /// files.
+    pub fn download_schemas(
+        &mut self,
+        cache: &SchemaCache,
+        format: SchemaFormat,
+    ) -> Result<Vec<PathBuf>, NetconfClientError> {
+        let mut downloaded = Vec::new();
+        for schema in self.schema_ids()? {
+            if cache.contains(&schema, format).unwrap_or(true) {
+                continue;
+            }
+            let rsp =
+                self.get_schema(&schema.identifier, schema.revision.as_deref(), Some(format))?;
+            downloaded.push(cache.store(&schema, format, &rsp.schema)?);
+        }
+        Ok(downloaded)
+    }
+
+    fn schema_ids(&mut self) -> Result<Vec<SchemaId>, NetconfClientError> {
+        let (mut schemas, yang_library) = match self.server_capabilities() {
+            Some(capabilities) => (
+                schema_cache::hello_schemas(capabilities),
+                capabilities
+                    .yang_library()
+                    .map(|yang_library| yang_library.version),
+            ),
+            None => (Vec::new(), None),
         };
-        let cmd = to_string(&model).unwrap() + consts::MESSAGE_SEPARATOR;
-        self.send(&cmd)?;
-        let reply: CopyConfigRsp = quick_xml::de::from_str(&self.get_reply()?).unwrap();
-        self.check_response_message_id(&reply)?;
-        NetconfClient::make_return(reply)
+        match yang_library {
+            Some(YangLibraryVersion::V1_1) => schemas.extend(schema_cache::yang_library_schemas(
+                &self.get_yang_library()?,
+            )),
+            Some(YangLibraryVersion::V1_0) => schemas.extend(schema_cache::modules_state_schemas(
+                &self.get_modules_state()?,
+            )),
+            None => (),
+        }
+        schemas.sort();
+        schemas.dedup();
+        Ok(schemas)
     }
 
-    pub fn get_data(text: &str) -> Option<&str> {
-        let begin_begin_tag = "<data";
-        let end_begin_tag = ">";
-        let value_begin = text.find(begin_begin_tag).unwrap();
-        let value_end = text[value_begin..]
-            .find(end_begin_tag)
-            .map(|i| i + value_begin)
-            .unwrap();
-        let end_element = text.find("</data>").unwrap();
-        if value_end + 1 > end_element {
-            return None;
+    pub fn raw_rpc(&mut self, data: &str) -> Result<RawRpcRsp, NetconfClientError> {
+        self.id += 1;
+        let reply = self.request(&messages::raw_rpc(self.id, data))?;
+        messages::parse_raw_rpc_reply(&reply, self.id)
+    }
+
+    /// Invokes an RPC defined in the module with the given namespace. The
+    /// output leaves of the reply are deserialized into `Rsp`.
+    pub fn rpc<Req: Serialize, Rsp: DeserializeOwned>(
+        &mut self,
+        namespace: &str,
+        input: &Req,
+    ) -> Result<Rsp, NetconfClientError> {
+        let data = messages::rpc_input(namespace, input)?;
+        self.id += 1;
+        let reply = self.request(&messages::raw_rpc(self.id, &data))?;
+        messages::parse_rpc_reply(&reply, self.id)
+    }
+
+    /// Invokes a YANG 1.1 action on the data node at `path`. The output
+    /// leaves of the reply are deserialized into `Rsp`.
+    pub fn action<Req: Serialize, Rsp: DeserializeOwned>(
+        &mut self,
+        path: &ActionPath,
+        input: &Req,
+    ) -> Result<Rsp, NetconfClientError> {
+        let data = messages::action_input(path, input)?;
+        self.id += 1;
+        let reply = self.request(&messages::raw_rpc(self.id, &data))?;
+        messages::parse_rpc_reply(&reply, self.id)
+    }
+
+    pub fn get_modules_state(&mut self) -> Result<ModulesState, NetconfClientError> {
+        let content_id = self.session.as_ref().and_then(Session::content_id);
+        if let Some(state) = self.yang_library_cache.modules_state(content_id) {
+            return Ok(state.clone());
         }
-        Some(&text[value_end + 1..end_element])
+        self.id += 1;
+        let reply = self.request(&messages::get_modules_state(self.id))?;
+        let state: ModulesState = messages::parse_data_reply(&reply, self.id)?;
+        self.yang_library_cache.modules_state = Some(state.clone());
+        Ok(state)
     }
 
-    fn check_response_message_id<T: RpcRsp>(&self, rsp: &T) -> Result<(), NetconfClientError> {
-        let message_id = rsp.get_message_id().expect("no message id");
-        if self.id != message_id {
-            return Err(NetconfResponseIdError {
-                err: format!("request id: {} response id: {}", self.id, message_id),
-            });
+    pub fn get_yang_library(&mut self) -> Result<YangLibraryContent, NetconfClientError> {
+        let content_id = self.session.as_ref().and_then(Session::content_id);
+        if let Some(library) = self.yang_library_cache.yang_library(content_id) {
+            return Ok(library.clone());
         }
-        Ok(())
+        self.id += 1;
+        let reply = self.request(&messages::get_yang_library(self.id))?;
+        let library: YangLibraryContent = messages::parse_data_reply(&reply, self.id)?;
+        self.yang_library_cache.yang_library = Some(library.clone());
+        Ok(library)
     }
 
-    fn make_return<T: RpcRsp>(rsp: T) -> Result<T, NetconfClientError> {
-        if rsp.is_ok() {
-            Ok(rsp)
-        } else {
-            Err(NetconfError {
-                err: rsp.get_error().unwrap().to_vec(),
-            })
+    pub fn invalidate_yang_library_cache(&mut self) {
+        self.yang_library_cache = YangLibraryCache::default();
+    }
+
+    pub fn get_data(text: &str) -> Option<&str> {
+        messages::data_content(text)
+    }
+}
+
+/// Blocking iterator over received notifications, ending once no
+/// subscription is active or after the first error.
+pub struct Notifications<'a> {
+    client: &'a mut NetconfClient,
+    failed: bool,
+}
+
+impl Iterator for Notifications<'_> {
+    type Item = Result<Notification, NetconfClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            let next = match self.client.next_notification() {
+                // The subscription is just idle.
+                Err(NetconfClientError::IOError(err)) if err.kind() == io::ErrorKind::TimedOut => {
+                    continue
+                }
+                next => next.transpose(),
+            };
+            self.failed = matches!(next, Some(Err(_)));
+            return next;
         }
     }
 }
 
 impl Drop for NetconfClient {
     fn drop(&mut self) {
-        if self.session_id.is_some() {
+        if self.session.is_some() {
             if let Result::Err(err) = self.close_session() {
-                println!("close_session error: {}", err.to_string());
+                println!("close_session error: {}", err);
             }
         }
     }
@@ -0,0 +1,159 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+pub const NOTIFICATION_XMLNS: &str = "urn:ietf:params:xml:ns:netconf:notification:1.0";
+pub const NETMOD_NOTIFICATION_XMLNS: &str = "urn:ietf:params:xml:ns:netmod:notification";
+
+/// `<notification>` message received from the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub event_time: String,
+    /// Local name of the event element, e.g. `netconf-config-change`.
+    pub event_name: String,
+    pub event_namespace: Option<String>,
+    /// Event element including its start and end tags.
+    pub event: String,
+    pub raw: String,
+}
+
+impl Notification {
+    pub fn parse(raw: &str) -> Notification {
+        let mut notification = Notification {
+            event_time: String::new(),
+            event_name: String::new(),
+            event_namespace: None,
+            event: String::new(),
+            raw: raw.to_string(),
+        };
+        let mut reader = Reader::from_str(raw);
+        let mut buf = Vec::new();
+        let mut depth = 0;
+        let mut in_event_time = false;
+        let mut event_start = None;
+        loop {
+            let position = reader.buffer_position();
+            match reader.read_event(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    depth += 1;
+                    if depth == 2 {
+                        if e.local_name() == b"eventTime" {
+                            in_event_time = true;
+                        } else if event_start.is_none() {
+                            notification.event_name =
+                                String::from_utf8_lossy(e.local_name()).into_owned();
+                            notification.event_namespace = namespace(e);
+                            event_start = Some(position);
+                        }
+                    }
+                }
+                Ok(Event::Empty(ref e))
+                    if depth == 1 && event_start.is_none() && e.local_name() != b"eventTime" =>
+                {
+                    notification.event_name = String::from_utf8_lossy(e.local_name()).into_owned();
+                    notification.event_namespace = namespace(e);
+                    notification.event = raw[position..reader.buffer_position()].to_string();
+                    event_start = Some(position);
+                }
+                Ok(Event::Text(ref e)) if in_event_time => {
+                    notification.event_time = e
+                        .unescaped()
+                        .map(|text| String::from_utf8_lossy(&text).trim().to_string())
+                        .unwrap_or_default();
+                }
+                Ok(Event::End(_)) => {
+                    if depth == 2 {
+                        if in_event_time {
+                            in_event_time = false;
+                        } else if let (Some(start), true) =
+                            (event_start, notification.event.is_empty())
+                        {
+                            notification.event = raw[start..reader.buffer_position()].to_string();
+                        }
+                    }
+                    depth -= 1;
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        notification
+    }
+
+    /// RFC 5277 `<replayComplete>`, sent after all replayed events.
+    pub fn is_replay_complete(&self) -> bool {
+        self.event_name == "replayComplete"
+    }
+
+    /// RFC 5277 `<notificationComplete>`, sent when the subscription ends.
+    pub fn is_notification_complete(&self) -> bool {
+        self.event_name == "notificationComplete"
+    }
+}
+
+fn namespace(element: &quick_xml::events::BytesStart) -> Option<String> {
+    let prefix = element
+        .name()
+        .iter()
+        .position(|b| *b == b':')
+        .map(|index| &element.name()[..index]);
+    let key = match prefix {
+        Some(prefix) => [b"xmlns:", prefix].concat(),
+        None => b"xmlns".to_vec(),
+    };
+    element
+        .attributes()
+        .filter_map(|attr| attr.ok())
+        .find(|attr| attr.key == key.as_slice())
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Notification with the given event element, for tests.
+#[cfg(test)]
+pub(crate) fn notification(event_time: &str, event: &str) -> Notification {
+    Notification::parse(&format!(
+        r#"<notification xmlns="{}"><eventTime>{}</eventTime>{}</notification>"#,
+        NOTIFICATION_XMLNS, event_time, event
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_notification() {
+        let xml = r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0">
+    <eventTime>2021-06-01T10:00:00Z</eventTime>
+    <event xmlns="http://example.com/event/1.0">
+        <eventClass>fault</eventClass>
+        <reportingEntity><card>Ethernet0</card></reportingEntity>
+    </event>
+</notification>"#;
+        let notification = Notification::parse(xml);
+        assert_eq!(notification.event_time, "2021-06-01T10:00:00Z");
+        assert_eq!(notification.event_name, "event");
+        assert_eq!(
+            notification.event_namespace,
+            Some("http://example.com/event/1.0".to_string())
+        );
+        assert!(notification.event.starts_with(r#"<event xmlns="#));
+        assert!(notification.event.ends_with("</event>"));
+        assert!(!notification.is_replay_complete());
+    }
+
+    #[test]
+    fn parse_replay_complete() {
+        let xml = r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0"><eventTime>2021-06-01T10:00:00Z</eventTime><replayComplete xmlns="urn:ietf:params:xml:ns:netmod:notification"/></notification>"#;
+        let notification = Notification::parse(xml);
+        assert!(notification.is_replay_complete());
+        assert_eq!(
+            notification.event,
+            r#"<replayComplete xmlns="urn:ietf:params:xml:ns:netmod:notification"/>"#
+        );
+        assert_eq!(
+            notification.event_namespace.as_deref(),
+            Some(NETMOD_NOTIFICATION_XMLNS)
+        );
+    }
+}
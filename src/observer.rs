@@ -0,0 +1,142 @@
+use crate::messages;
+use regex::Regex;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const DEFAULT_SENSITIVE_LEAVES: [&str; 6] = [
+    "password",
+    "secret",
+    "passphrase",
+    "private-key",
+    "pre-shared-key",
+    "shared-secret",
+];
+const REDACTED: &str = "***";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Outbound,
+    Inbound,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedMessage {
+    pub direction: Direction,
+    pub session_id: Option<u32>,
+    pub message_id: Option<u32>,
+    pub timestamp: SystemTime,
+    pub message: String,
+}
+
+/// Receives every NETCONF message sent or received by a client, after
+/// sensitive leaves have been redacted.
+pub trait MessageObserver: Send + Sync {
+    fn on_message(&self, message: &TracedMessage);
+}
+
+/// Replaces the text of sensitive leaves, matched by local name with any
+/// namespace prefix, before messages are passed to a [`MessageObserver`].
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    leaves: Vec<String>,
+    pattern: Option<Regex>,
+}
+
+impl Redactor {
+    pub fn new<I, S>(leaves: I) -> Redactor
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let leaves: Vec<String> = leaves.into_iter().map(Into::into).collect();
+        let pattern = if leaves.is_empty() {
+            None
+        } else {
+            let names = leaves
+                .iter()
+                .map(|leaf| regex::escape(leaf))
+                .collect::<Vec<_>>()
+                .join("|");
+            let pattern = format!(
+                r"<((?:[\w.-]+:)?(?:{names}))(\s[^>]*)?>[^<]*</(?:[\w.-]+:)?(?:{names})\s*>",
+                names = names
+            );
+            Some(Regex::new(&pattern).unwrap())
+        };
+        Redactor { leaves, pattern }
+    }
+
+    pub fn with_leaves<I, S>(self, leaves: I) -> Redactor
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Redactor::new(
+            self.leaves
+                .into_iter()
+                .chain(leaves.into_iter().map(Into::into)),
+        )
+    }
+
+    pub fn leaves(&self) -> &[String] {
+        &self.leaves
+    }
+
+    pub fn redact(&self, message: &str) -> String {
+        match &self.pattern {
+            Some(pattern) => pattern
+                .replace_all(message, format!("<$1$2>{}</$1>", REDACTED).as_str())
+                .into_owned(),
+            None => message.to_string(),
+        }
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Redactor::new(DEFAULT_SENSITIVE_LEAVES.iter().copied())
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Tracer {
+    observer: Arc<dyn MessageObserver>,
+    redactor: Redactor,
+}
+
+impl Tracer {
+    pub fn new(observer: Arc<dyn MessageObserver>, redactor: Redactor) -> Tracer {
+        Tracer { observer, redactor }
+    }
+
+    pub fn trace(&self, direction: Direction, session_id: Option<u32>, message: &str) {
+        self.observer.on_message(&TracedMessage {
+            direction,
+            session_id,
+            message_id: messages::reply_message_id(message),
+            timestamp: SystemTime::now(),
+            message: self.redactor.redact(message),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_default_leaves() {
+        let xml = r#"<config><user><name>admin</name><password>admin123</password><auth:shared-secret xmlns:auth="urn:auth">s3cr3t</auth:shared-secret></user></config>"#;
+        let expected = r#"<config><user><name>admin</name><password>***</password><auth:shared-secret xmlns:auth="urn:auth">***</auth:shared-secret></user></config>"#;
+        assert_eq!(Redactor::default().redact(xml), expected);
+    }
+
+    #[test]
+    fn redact_configured_leaves() {
+        let redactor = Redactor::default().with_leaves(vec!["community"]);
+        let xml = r#"<snmp><community>public</community><name>public</name></snmp>"#;
+        let expected = r#"<snmp><community>***</community><name>public</name></snmp>"#;
+        assert_eq!(redactor.redact(xml), expected);
+        assert_eq!(Redactor::new(Vec::<String>::new()).redact(xml), xml);
+    }
+}
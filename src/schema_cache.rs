@@ -0,0 +1,213 @@
+use crate::capabilities::Capabilities;
+use crate::models::requests::SchemaFormat;
+use crate::models::yang_library::{ModulesState, YangLibraryContent};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Identifier and revision of a schema which can be downloaded with
+/// `<get-schema>`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaId {
+    pub identifier: String,
+    pub revision: Option<String>,
+}
+
+/// Directory with downloaded schemas stored as `module@revision.<format>`.
+#[derive(Debug, Clone)]
+pub struct SchemaCache {
+    directory: PathBuf,
+}
+
+impl SchemaCache {
+    pub fn new<P: AsRef<Path>>(directory: P) -> SchemaCache {
+        SchemaCache {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Fails with `InvalidInput` when the identifier is not a YANG identifier
+    /// or the revision is not a `YYYY-MM-DD` date, since both come from the
+    /// server and must not escape the cache directory.
+    pub fn path(&self, schema: &SchemaId, format: SchemaFormat) -> Result<PathBuf, io::Error> {
+        if !is_identifier(&schema.identifier) {
+            return Err(invalid_schema("identifier", &schema.identifier));
+        }
+        let name = match &schema.revision {
+            Some(revision) if !is_revision(revision) => {
+                return Err(invalid_schema("revision", revision))
+            }
+            Some(revision) => format!("{}@{}", schema.identifier, revision),
+            None => schema.identifier.clone(),
+        };
+        Ok(self
+            .directory
+            .join(format!("{}.{}", name, format.extension())))
+    }
+
+    pub fn contains(&self, schema: &SchemaId, format: SchemaFormat) -> Result<bool, io::Error> {
+        Ok(self.path(schema, format)?.is_file())
+    }
+
+    pub fn store(
+        &self,
+        schema: &SchemaId,
+        format: SchemaFormat,
+        text: &str,
+    ) -> Result<PathBuf, io::Error> {
+        let path = self.path(schema, format)?;
+        fs::create_dir_all(&self.directory)?;
+        fs::write(&path, text)?;
+        Ok(path)
+    }
+}
+
+fn is_identifier(identifier: &str) -> bool {
+    let mut chars = identifier.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+fn is_revision(revision: &str) -> bool {
+    let bytes = revision.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, byte)| match i {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+fn invalid_schema(field: &str, value: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid schema {} {:?}", field, value),
+    )
+}
+
+pub(crate) fn hello_schemas(capabilities: &Capabilities) -> Vec<SchemaId> {
+    capabilities
+        .modules()
+        .map(|module| SchemaId {
+            identifier: module.name.clone(),
+            revision: module.revision.clone(),
+        })
+        .collect()
+}
+
+pub(crate) fn yang_library_schemas(library: &YangLibraryContent) -> Vec<SchemaId> {
+    let mut schemas = Vec::new();
+    for set in &library.module_sets {
+        let modules = set
+            .modules
+            .iter()
+            .map(|module| (&module.name, &module.revision, &module.submodules))
+            .chain(
+                set.import_only_modules
+                    .iter()
+                    .map(|module| (&module.name, &module.revision, &module.submodules)),
+            );
+        for (name, revision, submodules) in modules {
+            schemas.push(SchemaId {
+                identifier: name.clone(),
+                revision: revision.clone(),
+            });
+            schemas.extend(submodules.iter().map(|submodule| SchemaId {
+                identifier: submodule.name.clone(),
+                revision: submodule.revision.clone(),
+            }));
+        }
+    }
+    schemas
+}
+
+pub(crate) fn modules_state_schemas(state: &ModulesState) -> Vec<SchemaId> {
+    let mut schemas = Vec::new();
+    for module in &state.modules {
+        schemas.push(SchemaId {
+            identifier: module.name.clone(),
+            revision: module
+                .revision
+                .clone()
+                .filter(|revision| !revision.is_empty()),
+        });
+        schemas.extend(module.submodules.iter().map(|submodule| {
+            SchemaId {
+                identifier: submodule.name.clone(),
+                revision: submodule
+                    .revision
+                    .clone()
+                    .filter(|revision| !revision.is_empty()),
+            }
+        }));
+    }
+    schemas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_paths() {
+        let directory = std::env::temp_dir().join(format!("schema-cache-{}", std::process::id()));
+        let cache = SchemaCache::new(&directory);
+        let schema = SchemaId {
+            identifier: "ietf-interfaces".to_string(),
+            revision: Some("2018-02-20".to_string()),
+        };
+        assert_eq!(
+            cache.path(&schema, SchemaFormat::Yang).unwrap(),
+            directory.join("ietf-interfaces@2018-02-20.yang")
+        );
+        assert!(!cache.contains(&schema, SchemaFormat::Yang).unwrap());
+        cache
+            .store(&schema, SchemaFormat::Yang, "module ietf-interfaces {}")
+            .unwrap();
+        assert!(cache.contains(&schema, SchemaFormat::Yang).unwrap());
+        assert!(!cache.contains(&schema, SchemaFormat::Yin).unwrap());
+        let schema = SchemaId {
+            identifier: "test".to_string(),
+            revision: None,
+        };
+        assert_eq!(
+            cache.path(&schema, SchemaFormat::Yin).unwrap(),
+            directory.join("test.yin")
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn invalid_schema_ids() {
+        let directory =
+            std::env::temp_dir().join(format!("schema-cache-invalid-{}", std::process::id()));
+        let cache = SchemaCache::new(&directory);
+        for (identifier, revision) in [
+            ("../../etc/passwd", None),
+            ("/etc/passwd", None),
+            ("1module", None),
+            ("", None),
+            ("module/sub", None),
+            ("module", Some("2018-02-20/../..")),
+            ("module", Some("../2018-02")),
+            ("module", Some("20180220")),
+        ] {
+            let schema = SchemaId {
+                identifier: identifier.to_string(),
+                revision: revision.map(str::to_string),
+            };
+            assert_eq!(
+                cache.path(&schema, SchemaFormat::Yang).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+            assert!(cache.contains(&schema, SchemaFormat::Yang).is_err());
+            assert!(cache.store(&schema, SchemaFormat::Yang, "").is_err());
+        }
+        assert!(!directory.exists());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::capabilities::Capabilities;
+use crate::models::replies::HelloServer;
+use crate::models::yang_library::{ModulesState, YangLibraryContent};
+
+pub const BASE_1_0: &str = "urn:ietf:params:netconf:base:1.0";
+pub const BASE_1_1: &str = "urn:ietf:params:netconf:base:1.1";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framing {
+    EndOfMessage,
+    Chunked,
+}
+
+/// Capabilities advertised in the client `<hello>`. By default both
+/// `base:1.0` and `base:1.1` are advertised.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientCapabilities {
+    base_1_1: bool,
+    extra: Vec<String>,
+}
+
+impl ClientCapabilities {
+    pub fn with_base_1_1(mut self, base_1_1: bool) -> ClientCapabilities {
+        self.base_1_1 = base_1_1;
+        self
+    }
+
+    pub fn with_capability(mut self, capability: &str) -> ClientCapabilities {
+        if !self.extra.iter().any(|extra| extra == capability) {
+            self.extra.push(capability.to_string());
+        }
+        self
+    }
+
+    pub fn capabilities(&self) -> Vec<String> {
+        let mut capabilities = vec![BASE_1_0.to_string()];
+        if self.base_1_1 {
+            capabilities.push(BASE_1_1.to_string());
+        }
+        capabilities.extend(
+            self.extra
+                .iter()
+                .filter(|extra| *extra != BASE_1_0 && *extra != BASE_1_1)
+                .cloned(),
+        );
+        capabilities
+    }
+}
+
+impl Default for ClientCapabilities {
+    fn default() -> Self {
+        ClientCapabilities {
+            base_1_1: true,
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// Result of the `<hello>` exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub session_id: u32,
+    pub client_capabilities: Vec<String>,
+    pub server_capabilities: Capabilities,
+    pub framing: Framing,
+}
+
+impl Session {
+    pub(crate) fn negotiate(client_capabilities: Vec<String>, hello: &HelloServer) -> Session {
+        let server_capabilities = Capabilities::parse(&hello.capabilities.capabilities);
+        let client_base_1_1 = client_capabilities.iter().any(|c| c == BASE_1_1);
+        let framing = if client_base_1_1 && server_capabilities.base_1_1() {
+            Framing::Chunked
+        } else {
+            Framing::EndOfMessage
+        };
+        Session {
+            session_id: hello.session_id,
+            client_capabilities,
+            server_capabilities,
+            framing,
+        }
+    }
+
+    pub(crate) fn content_id(&self) -> Option<&str> {
+        self.server_capabilities
+            .yang_library()
+            .and_then(|yang_library| yang_library.content_id.as_deref())
+    }
+}
+
+/// YANG library retrieved during a session. Entries are valid as long as
+/// the id they were retrieved with matches the content-id from the hello.
+#[derive(Debug, Default)]
+pub(crate) struct YangLibraryCache {
+    pub modules_state: Option<ModulesState>,
+    pub yang_library: Option<YangLibraryContent>,
+}
+
+impl YangLibraryCache {
+    pub fn modules_state(&self, content_id: Option<&str>) -> Option<&ModulesState> {
+        self.modules_state
+            .as_ref()
+            .filter(|state| !content_id.is_some_and(|id| id != state.module_set_id))
+    }
+
+    pub fn yang_library(&self, content_id: Option<&str>) -> Option<&YangLibraryContent> {
+        self.yang_library
+            .as_ref()
+            .filter(|library| !content_id.is_some_and(|id| id != library.content_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::replies::CapabilitiesServer;
+
+    fn server_hello(capabilities: &[&str]) -> HelloServer {
+        HelloServer {
+            xmlns: "urn:ietf:params:xml:ns:netconf:base:1.0".to_string(),
+            capabilities: CapabilitiesServer {
+                capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            },
+            session_id: 4,
+            rpc_error: None,
+        }
+    }
+
+    #[test]
+    fn client_capabilities() {
+        assert_eq!(
+            ClientCapabilities::default()
+                .with_capability("urn:ietf:params:netconf:capability:startup:1.0")
+                .with_capability(BASE_1_1)
+                .capabilities(),
+            vec![
+                BASE_1_0,
+                BASE_1_1,
+                "urn:ietf:params:netconf:capability:startup:1.0"
+            ]
+        );
+        assert_eq!(
+            ClientCapabilities::default()
+                .with_base_1_1(false)
+                .capabilities(),
+            vec![BASE_1_0]
+        );
+    }
+
+    #[test]
+    fn negotiate_framing() {
+        let client = ClientCapabilities::default().capabilities();
+        let session = Session::negotiate(client.clone(), &server_hello(&[BASE_1_0, BASE_1_1]));
+        assert_eq!(session.session_id, 4);
+        assert_eq!(session.framing, Framing::Chunked);
+        let session = Session::negotiate(client, &server_hello(&[BASE_1_0]));
+        assert_eq!(session.framing, Framing::EndOfMessage);
+        let client = ClientCapabilities::default()
+            .with_base_1_1(false)
+            .capabilities();
+        let session = Session::negotiate(client, &server_hello(&[BASE_1_0, BASE_1_1]));
+        assert_eq!(session.framing, Framing::EndOfMessage);
+    }
+
+    #[test]
+    fn yang_library_cache() {
+        let mut cache = YangLibraryCache::default();
+        assert!(cache.modules_state(None).is_none());
+        cache.modules_state = Some(ModulesState {
+            module_set_id: "14".to_string(),
+            modules: Vec::new(),
+        });
+        assert!(cache.modules_state(None).is_some());
+        assert!(cache.modules_state(Some("14")).is_some());
+        assert!(cache.modules_state(Some("15")).is_none());
+    }
+}
@@ -0,0 +1,980 @@
+use crate::capabilities::{Capabilities, CapabilityPolicy, WithDefaultsMode, YangLibraryVersion};
+use crate::consts::SSH_TIMEOUT;
+use crate::errors::NetconfClientError;
+use crate::errors::NetconfClientError::SSHClientError;
+use crate::framing::{self, MessageDecoder};
+use crate::messages;
+use crate::models::netconf_monitoring::{
+    DatastoreState, NetconfState, SchemaState, SessionState, Statistics,
+};
+use crate::models::subscribed_notifications::SubscriptionId;
+use crate::models::yang_library::{ModulesState, YangLibraryContent};
+use crate::models::{replies::*, requests::*};
+use crate::notification::Notification;
+use crate::observer::{Direction, MessageObserver, Redactor, Tracer};
+use crate::schema_cache::{self, SchemaCache, SchemaId};
+use crate::session::{ClientCapabilities, Framing, Session, YangLibraryCache};
+use crate::ssh_client::SSHClient;
+use mio::net::TcpStream;
+use mio::{Events, Interest, Poll, Token, Waker};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+const SOCKET: Token = Token(0);
+const WAKER: Token = Token(1);
+/// Readiness is edge-triggered, the timeout only guards against data left in
+/// the libssh2 buffers without a new edge.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+type ReplySender = Sender<Result<String, NetconfClientError>>;
+type SharedTracer = Arc<RwLock<Option<Tracer>>>;
+
+enum Command {
+    Request {
+        message_id: u32,
+        data: String,
+        reply: ReplySender,
+    },
+    Disconnect {
+        reply: Sender<Result<(), NetconfClientError>>,
+    },
+}
+
+/// Cloneable handle to a single NETCONF session which can be used from many
+/// threads at once.
+///
+/// The SSH transport is owned by a background thread which sends requests,
+/// dispatches replies to the waiting callers by message-id and forwards
+/// `<notification>` messages to the channel returned by
+/// [`take_notifications`](SharedNetconfClient::take_notifications).
+#[derive(Clone)]
+pub struct SharedNetconfClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    id: Arc<AtomicU32>,
+    session: Session,
+    capability_policy: RwLock<CapabilityPolicy>,
+    request_timeout: RwLock<Option<Duration>>,
+    yang_library_cache: Mutex<YangLibraryCache>,
+    commands: Sender<Command>,
+    waker: Arc<Waker>,
+    notifications: Mutex<Option<Receiver<Notification>>>,
+    subscription_active: Arc<AtomicBool>,
+    tracer: SharedTracer,
+}
+
+impl SharedNetconfClient {
+    pub fn connect(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+    ) -> Result<SharedNetconfClient, NetconfClientError> {
+        SharedNetconfClient::connect_with_capabilities(
+            host,
+            port,
+            user,
+            password,
+            ClientCapabilities::default(),
+        )
+    }
+
+    pub fn connect_with_capabilities(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+        capabilities: ClientCapabilities,
+    ) -> Result<SharedNetconfClient, NetconfClientError> {
+        let mut ssh_client = SSHClient::create(host, port, user, password);
+        ssh_client.connect()?;
+        let mut decoder = MessageDecoder::default();
+        let hello = messages::parse_hello(&read_message(&mut ssh_client, &mut decoder)?)?;
+        let capabilities = capabilities.capabilities();
+        ssh_client.write_all(
+            framing::encode(&messages::hello(&capabilities), Framing::EndOfMessage).as_bytes(),
+        )?;
+        let session = Session::negotiate(capabilities, &hello);
+        decoder.set_framing(session.framing);
+        ssh_client.set_blocking(false);
+        let stream = ssh_client.try_clone_stream()?;
+        stream.set_nonblocking(true)?;
+        let mut socket = TcpStream::from_std(stream);
+        let poll = Poll::new()?;
+        poll.registry()
+            .register(&mut socket, SOCKET, Interest::READABLE | Interest::WRITABLE)?;
+        let waker = Arc::new(Waker::new(poll.registry(), WAKER)?);
+
+        let id = Arc::new(AtomicU32::new(0));
+        let (commands_tx, commands_rx) = mpsc::channel();
+        let (notifications_tx, notifications_rx) = mpsc::channel();
+        let tracer = SharedTracer::default();
+        let subscription_active = Arc::new(AtomicBool::new(false));
+        let reader = Reader {
+            id: id.clone(),
+            session: session.clone(),
+            ssh_client,
+            decoder,
+            poll,
+            events: Events::with_capacity(16),
+            _socket: socket,
+            commands: commands_rx,
+            notifications: notifications_tx,
+            subscription_active: subscription_active.clone(),
+            pending: PendingReplies::default(),
+            tracer: tracer.clone(),
+        };
+        thread::spawn(move || reader.run());
+
+        Ok(SharedNetconfClient {
+            inner: Arc::new(Inner {
+                id,
+                session,
+                capability_policy: RwLock::new(CapabilityPolicy::default()),
+                request_timeout: RwLock::new(Some(Duration::from_millis(SSH_TIMEOUT.into()))),
+                yang_library_cache: Mutex::new(YangLibraryCache::default()),
+                commands: commands_tx,
+                waker,
+                notifications: Mutex::new(Some(notifications_rx)),
+                subscription_active,
+                tracer,
+            }),
+        })
+    }
+
+    pub fn get_session_id(&self) -> u32 {
+        self.inner.session.session_id
+    }
+
+    pub fn get_session(&self) -> &Session {
+        &self.inner.session
+    }
+
+    pub fn server_capabilities(&self) -> &Capabilities {
+        &self.inner.session.server_capabilities
+    }
+
+    pub fn set_capability_policy(&self, policy: CapabilityPolicy) {
+        *self.inner.capability_policy.write().unwrap() = policy;
+    }
+
+    /// How long a caller waits for its reply, by default as long as the SSH
+    /// timeout of the other clients. `None` waits until the session ends.
+    pub fn set_request_timeout(&self, timeout: Option<Duration>) {
+        *self.inner.request_timeout.write().unwrap() = timeout;
+    }
+
+    pub fn set_observer(&self, observer: Arc<dyn MessageObserver>, redactor: Redactor) {
+        *self.inner.tracer.write().unwrap() = Some(Tracer::new(observer, redactor));
+    }
+
+    /// Returns the receiving end of the notification channel. Only the first
+    /// call returns `Some`.
+    pub fn take_notifications(&self) -> Option<Receiver<Notification>> {
+        self.inner.notifications.lock().unwrap().take()
+    }
+
+    pub fn subscription_active(&self) -> bool {
+        self.inner.subscription_active.load(Ordering::SeqCst)
+    }
+
+    /// Subscribes to an event stream. Notifications are delivered to the
+    /// channel returned by
+    /// [`take_notifications`](SharedNetconfClient::take_notifications); the
+    /// last one of a subscription with a stop time is `<notificationComplete>`.
+    pub fn create_subscription(
+        &self,
+        stream: Option<&str>,
+        filter: Option<NotificationFilter>,
+        start_time: Option<&str>,
+        stop_time: Option<&str>,
+    ) -> Result<CreateSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_create_subscription(&filter))?;
+        // marked active before sending so that a `<notificationComplete>`
+        // dispatched right after the reply is not overwritten
+        let previous = self.inner.subscription_active.swap(true, Ordering::SeqCst);
+        let reply = self
+            .send_request(|id| {
+                Ok(messages::create_subscription(
+                    id, stream, filter, start_time, stop_time,
+                ))
+            })
+            .and_then(|(id, reply)| messages::parse_reply(&reply, id));
+        if reply.is_err() {
+            self.inner
+                .subscription_active
+                .store(previous, Ordering::SeqCst);
+        }
+        reply
+    }
+
+    pub fn establish_subscription(
+        &self,
+        subscription: StreamSubscription,
+    ) -> Result<EstablishSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities
+                .check_subscribed_notifications(&subscription.filter, "establish-subscription")
+        })?;
+        let (id, reply) = self.request(|id| messages::establish_subscription(id, subscription))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    /// Establishes a YANG-Push subscription. Updates are received as
+    /// notifications which can be decoded with
+    /// [`YangPushUpdate`](crate::models::yang_push::YangPushUpdate).
+    pub fn establish_datastore_subscription(
+        &self,
+        subscription: DatastoreSubscription,
+    ) -> Result<EstablishSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_yang_push(&subscription))?;
+        let (id, reply) =
+            self.request(|id| messages::establish_datastore_subscription(id, subscription))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn modify_subscription(
+        &self,
+        subscription_id: SubscriptionId,
+        filter: Option<NotificationFilter>,
+        stop_time: Option<&str>,
+    ) -> Result<ModifySubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&filter, "modify-subscription")
+        })?;
+        let (id, reply) = self
+            .request(|id| messages::modify_subscription(id, subscription_id, filter, stop_time))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn delete_subscription(
+        &self,
+        subscription_id: SubscriptionId,
+    ) -> Result<DeleteSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&None, "delete-subscription")
+        })?;
+        let (id, reply) = self.request(|id| messages::delete_subscription(id, subscription_id))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn kill_subscription(
+        &self,
+        subscription_id: SubscriptionId,
+    ) -> Result<KillSubscriptionRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_subscribed_notifications(&None, "kill-subscription")
+        })?;
+        let (id, reply) = self.request(|id| messages::kill_subscription(id, subscription_id))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    fn check_capabilities<F>(&self, check: F) -> Result<(), NetconfClientError>
+    where
+        F: FnOnce(&Capabilities) -> Result<(), NetconfClientError>,
+    {
+        match *self.inner.capability_policy.read().unwrap() {
+            CapabilityPolicy::Strict => check(&self.inner.session.server_capabilities),
+            CapabilityPolicy::Lenient => Ok(()),
+        }
+    }
+
+    fn declare_module_prefixes(&self, mut filter: Option<Filter>) -> Option<Filter> {
+        if let Some(filter) = filter.as_mut() {
+            self.server_capabilities().declare_module_prefixes(filter);
+        }
+        filter
+    }
+
+    fn request<F>(&self, build: F) -> Result<(u32, String), NetconfClientError>
+    where
+        F: FnOnce(u32) -> String,
+    {
+        self.send_request(|message_id| {
+            let data = build(message_id);
+            if self.subscription_active() {
+                let operation = messages::rpc_operation(&data).unwrap_or_default();
+                self.check_capabilities(|capabilities| capabilities.check_interleave(&operation))?;
+            }
+            Ok(data)
+        })
+    }
+
+    fn send_request<F>(&self, build: F) -> Result<(u32, String), NetconfClientError>
+    where
+        F: FnOnce(u32) -> Result<String, NetconfClientError>,
+    {
+        let message_id = self.inner.id.fetch_add(1, Ordering::SeqCst) + 1;
+        let data = build(message_id)?;
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.inner.send_command(Command::Request {
+            message_id,
+            data,
+            reply: reply_tx,
+        })?;
+        let reply = self.wait_reply(reply_rx)??;
+        Ok((message_id, reply))
+    }
+
+    fn wait_reply<T>(&self, reply_rx: Receiver<T>) -> Result<T, NetconfClientError> {
+        match *self.inner.request_timeout.read().unwrap() {
+            Some(timeout) => reply_rx.recv_timeout(timeout).map_err(|err| match err {
+                RecvTimeoutError::Timeout => {
+                    io::Error::new(io::ErrorKind::TimedOut, "NETCONF reply timed out").into()
+                }
+                RecvTimeoutError::Disconnected => reader_terminated(),
+            }),
+            None => reply_rx.recv().map_err(|_| reader_terminated()),
+        }
+    }
+
+    pub fn kill_session(&self, session_id: u32) -> Result<KillSessionRsp, NetconfClientError> {
+        let (id, reply) = self.request(|id| messages::kill_session(id, session_id))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn close_session(&self) -> Result<CloseSessionRsp, NetconfClientError> {
+        let (id, reply) = self.request(messages::close_session)?;
+        let reply: CloseSessionRsp = messages::parse_reply(&reply, id)?;
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.inner
+            .send_command(Command::Disconnect { reply: reply_tx })?;
+        self.wait_reply(reply_rx)??;
+        Ok(reply)
+    }
+
+    pub fn get_config(
+        &self,
+        source: DatastoreType,
+        filter: Option<Filter>,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<GetConfigRsp, NetconfClientError> {
+        let filter = self.declare_module_prefixes(filter);
+        self.check_capabilities(|capabilities| {
+            capabilities.check_datastore(&source, "get-config")?;
+            capabilities.check_filter(&filter, "get-config")?;
+            capabilities.check_with_defaults(with_defaults, "get-config")
+        })?;
+        let (id, reply) =
+            self.request(|id| messages::get_config(id, source, filter, with_defaults))?;
+        messages::parse_get_config_reply(&reply, id, with_defaults)
+    }
+
+    pub fn get(
+        &self,
+        filter: Option<Filter>,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<GetRsp, NetconfClientError> {
+        let filter = self.declare_module_prefixes(filter);
+        self.check_capabilities(|capabilities| {
+            capabilities.check_filter(&filter, "get")?;
+            capabilities.check_with_defaults(with_defaults, "get")
+        })?;
+        let (id, reply) = self.request(|id| messages::get(id, filter, with_defaults))?;
+        messages::parse_get_reply(&reply, id, with_defaults)
+    }
+
+    /// RFC 8526 `<get-data>`, which can also read the `operational` and
+    /// `intended` datastores.
+    pub fn nmda_get_data(
+        &self,
+        datastore: Datastore,
+        mut options: GetDataOptions,
+    ) -> Result<GetDataRsp, NetconfClientError> {
+        options.filter = self.declare_module_prefixes(options.filter);
+        self.check_capabilities(|capabilities| capabilities.check_get_data(&options))?;
+        let with_defaults = options.with_defaults;
+        let (id, reply) = self.request(|id| messages::nmda_get_data(id, datastore, options))?;
+        messages::parse_get_data_reply(&reply, id, with_defaults)
+    }
+
+    pub fn edit_data(
+        &self,
+        datastore: Datastore,
+        data: String,
+        default_operation: Option<DefaultOperationType>,
+    ) -> Result<EditDataRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_edit_data())?;
+        let (id, reply) =
+            self.request(|id| messages::nmda_edit_data(id, datastore, data, default_operation))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn edit_config(
+        &self,
+        source: DatastoreType,
+        data: String,
+        default_operation: Option<DefaultOperationType>,
+        test_option: Option<TestOptionType>,
+        error_option: Option<ErrorOptionType>,
+    ) -> Result<EditConfigRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_edit_config(&source, &test_option, &error_option)
+        })?;
+        let (id, reply) = self.request(|id| {
+            messages::edit_config(
+                id,
+                source,
+                data,
+                default_operation,
+                test_option,
+                error_option,
+            )
+        })?;
+        messages::parse_reply(&reply, id)
+    }
+
+    /// `<edit-config>` with the configuration loaded by the server from `url`.
+    pub fn edit_config_url(
+        &self,
+        source: DatastoreType,
+        url: &str,
+        default_operation: Option<DefaultOperationType>,
+        test_option: Option<TestOptionType>,
+        error_option: Option<ErrorOptionType>,
+    ) -> Result<EditConfigRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_edit_config(&source, &test_option, &error_option)?;
+            capabilities.check_url(url, "edit-config")
+        })?;
+        let (id, reply) = self.request(|id| {
+            messages::edit_config_url(
+                id,
+                source,
+                url,
+                default_operation,
+                test_option,
+                error_option,
+            )
+        })?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn lock(&self, target: DatastoreType) -> Result<LockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_datastore(&target, "lock"))?;
+        let (id, reply) = self.request(|id| messages::lock(id, target))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn unlock(&self, target: DatastoreType) -> Result<UnlockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_datastore(&target, "unlock"))?;
+        let (id, reply) = self.request(|id| messages::unlock(id, target))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn partial_lock(
+        &self,
+        select: Vec<String>,
+        namespaces: &BTreeMap<String, String>,
+    ) -> Result<PartialLockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_partial_lock("partial-lock"))?;
+        let (id, reply) = self.request(|id| messages::partial_lock(id, select, namespaces))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn partial_unlock(&self, lock_id: u32) -> Result<PartialUnlockRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_partial_lock("partial-unlock"))?;
+        let (id, reply) = self.request(|id| messages::partial_unlock(id, lock_id))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn delete_config(
+        &self,
+        target: impl Into<ConfigTargetType>,
+    ) -> Result<DeleteConfigRsp, NetconfClientError> {
+        let target = target.into();
+        self.check_capabilities(|capabilities| capabilities.check_delete_config(&target))?;
+        let (id, reply) = self.request(|id| messages::delete_config(id, target))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn discard_changes(&self) -> Result<DiscardChangesRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_candidate("discard-changes"))?;
+        let (id, reply) = self.request(messages::discard_changes)?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn commit(&self) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_candidate("commit"))?;
+        let (id, reply) = self.request(|id| messages::commit(id, Commit::default()))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn confirmed_commit(
+        &self,
+        confirm_timeout: Option<u32>,
+        persist: Option<String>,
+    ) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_confirmed_commit("commit"))?;
+        let (id, reply) =
+            self.request(|id| messages::confirmed_commit(id, confirm_timeout, persist))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn confirm_commit(
+        &self,
+        persist_id: Option<String>,
+    ) -> Result<CommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_confirmed_commit("commit"))?;
+        let (id, reply) = self.request(|id| messages::confirm_commit(id, persist_id))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn cancel_commit(
+        &self,
+        persist_id: Option<String>,
+    ) -> Result<CancelCommitRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| {
+            capabilities.check_confirmed_commit("cancel-commit")
+        })?;
+        let (id, reply) = self.request(|id| messages::cancel_commit(id, persist_id))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn validate(&self, source: ValidateSourceType) -> Result<ValidateRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_validate(&source))?;
+        let (id, reply) = self.request(|id| messages::validate(id, source))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn copy_config(
+        &self,
+        target: impl Into<ConfigTargetType>,
+        source: CopyConfigSourceType,
+        with_defaults: Option<WithDefaultsMode>,
+    ) -> Result<CopyConfigRsp, NetconfClientError> {
+        let target = target.into();
+        self.check_capabilities(|capabilities| {
+            capabilities.check_copy_config(&target, &source)?;
+            capabilities.check_with_defaults(with_defaults, "copy-config")
+        })?;
+        let (id, reply) =
+            self.request(|id| messages::copy_config(id, target, source, with_defaults))?;
+        messages::parse_reply(&reply, id)
+    }
+
+    pub fn get_netconf_state(&self) -> Result<NetconfState, NetconfClientError> {
+        self.netconf_state(None)
+    }
+
+    pub fn get_sessions(&self) -> Result<Vec<SessionState>, NetconfClientError> {
+        let state = self.netconf_state(Some("sessions"))?;
+        Ok(state.sessions.unwrap_or_default().sessions)
+    }
+
+    pub fn get_datastores(&self) -> Result<Vec<DatastoreState>, NetconfClientError> {
+        let state = self.netconf_state(Some("datastores"))?;
+        Ok(state.datastores.unwrap_or_default().datastores)
+    }
+
+    pub fn get_statistics(&self) -> Result<Statistics, NetconfClientError> {
+        let state = self.netconf_state(Some("statistics"))?;
+        Ok(state.statistics.unwrap_or_default())
+    }
+
+    pub fn get_schema_list(&self) -> Result<Vec<SchemaState>, NetconfClientError> {
+        let state = self.netconf_state(Some("schemas"))?;
+        Ok(state.schemas.unwrap_or_default().schemas)
+    }
+
+    fn netconf_state(&self, container: Option<&str>) -> Result<NetconfState, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_monitoring("get"))?;
+        let (id, reply) = self.request(|id| messages::get_netconf_state(id, container))?;
+        messages::parse_data_reply(&reply, id)
+    }
+
+    pub fn get_schema(
+        &self,
+        identifier: &str,
+        version: Option<&str>,
+        format: Option<SchemaFormat>,
+    ) -> Result<GetSchemaRsp, NetconfClientError> {
+        self.check_capabilities(|capabilities| capabilities.check_monitoring("get-schema"))?;
+        let (id, reply) =
+            self.request(|id| messages::get_schema(id, identifier, version, format))?;
+        messages::parse_get_schema_reply(&reply, id)
+    }
+
+    /// Downloads schemas of all modules listed in the hello and in the YANG
+    /// library which are not in the cache yet. Schemas whose identifier or
+    /// revision is not a safe file name are skipped. Returns paths of new
+    /// files.
+    pub fn download_schemas(
+        &self,
+        cache: &SchemaCache,
+        format: SchemaFormat,
+    ) -> Result<Vec<PathBuf>, NetconfClientError> {
+        let mut downloaded = Vec::new();
+        for schema in self.schema_ids()? {
+            if cache.contains(&schema, format).unwrap_or(true) {
+                continue;
+            }
+            let rsp =
+                self.get_schema(&schema.identifier, schema.revision.as_deref(), Some(format))?;
+            downloaded.push(cache.store(&schema, format, &rsp.schema)?);
+        }
+        Ok(downloaded)
+    }
+
+    fn schema_ids(&self) -> Result<Vec<SchemaId>, NetconfClientError> {
+        let capabilities = self.server_capabilities();
+        let mut schemas = schema_cache::hello_schemas(capabilities);
+        match capabilities
+            .yang_library()
+            .map(|yang_library| yang_library.version)
+        {
+            Some(YangLibraryVersion::V1_1) => schemas.extend(schema_cache::yang_library_schemas(
+                &self.get_yang_library()?,
+            )),
+            Some(YangLibraryVersion::V1_0) => schemas.extend(schema_cache::modules_state_schemas(
+                &self.get_modules_state()?,
+            )),
+            None => (),
+        }
+        schemas.sort();
+        schemas.dedup();
+        Ok(schemas)
+    }
+
+    pub fn raw_rpc(&self, data: &str) -> Result<RawRpcRsp, NetconfClientError> {
+        let (id, reply) = self.request(|id| messages::raw_rpc(id, data))?;
+        messages::parse_raw_rpc_reply(&reply, id)
+    }
+
+    /// Invokes an RPC defined in the module with the given namespace. The
+    /// output leaves of the reply are deserialized into `Rsp`.
+    pub fn rpc<Req: Serialize, Rsp: DeserializeOwned>(
+        &self,
+        namespace: &str,
+        input: &Req,
+    ) -> Result<Rsp, NetconfClientError> {
+        let data = messages::rpc_input(namespace, input)?;
+        let (id, reply) = self.request(|id| messages::raw_rpc(id, &data))?;
+        messages::parse_rpc_reply(&reply, id)
+    }
+
+    /// Invokes a YANG 1.1 action on the data node at `path`. The output
+    /// leaves of the reply are deserialized into `Rsp`.
+    pub fn action<Req: Serialize, Rsp: DeserializeOwned>(
+        &self,
+        path: &ActionPath,
+        input: &Req,
+    ) -> Result<Rsp, NetconfClientError> {
+        let data = messages::action_input(path, input)?;
+        let (id, reply) = self.request(|id| messages::raw_rpc(id, &data))?;
+        messages::parse_rpc_reply(&reply, id)
+    }
+
+    pub fn get_modules_state(&self) -> Result<ModulesState, NetconfClientError> {
+        let content_id = self.inner.session.content_id();
+        if let Some(state) = self
+            .inner
+            .yang_library_cache
+            .lock()
+            .unwrap()
+            .modules_state(content_id)
+        {
+            return Ok(state.clone());
+        }
+        let (id, reply) = self.request(messages::get_modules_state)?;
+        let state: ModulesState = messages::parse_data_reply(&reply, id)?;
+        self.inner.yang_library_cache.lock().unwrap().modules_state = Some(state.clone());
+        Ok(state)
+    }
+
+    pub fn get_yang_library(&self) -> Result<YangLibraryContent, NetconfClientError> {
+        let content_id = self.inner.session.content_id();
+        if let Some(library) = self
+            .inner
+            .yang_library_cache
+            .lock()
+            .unwrap()
+            .yang_library(content_id)
+        {
+            return Ok(library.clone());
+        }
+        let (id, reply) = self.request(messages::get_yang_library)?;
+        let library: YangLibraryContent = messages::parse_data_reply(&reply, id)?;
+        self.inner.yang_library_cache.lock().unwrap().yang_library = Some(library.clone());
+        Ok(library)
+    }
+
+    pub fn invalidate_yang_library_cache(&self) {
+        *self.inner.yang_library_cache.lock().unwrap() = YangLibraryCache::default();
+    }
+}
+
+impl Inner {
+    fn send_command(&self, command: Command) -> Result<(), NetconfClientError> {
+        self.commands
+            .send(command)
+            .map_err(|_| reader_terminated())?;
+        self.waker.wake()?;
+        Ok(())
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Disconnect the channel before waking the reader so that it closes
+        // the session.
+        let (commands, _) = mpsc::channel();
+        drop(std::mem::replace(&mut self.commands, commands));
+        let _ = self.waker.wake();
+    }
+}
+
+struct Reader {
+    id: Arc<AtomicU32>,
+    session: Session,
+    ssh_client: SSHClient,
+    decoder: MessageDecoder,
+    poll: Poll,
+    events: Events,
+    /// Registered with `poll`, libssh2 does the actual IO on the socket.
+    _socket: TcpStream,
+    commands: Receiver<Command>,
+    notifications: Sender<Notification>,
+    subscription_active: Arc<AtomicBool>,
+    pending: PendingReplies,
+    tracer: SharedTracer,
+}
+
+impl Reader {
+    fn run(mut self) {
+        loop {
+            loop {
+                match self.commands.try_recv() {
+                    Ok(Command::Request {
+                        message_id,
+                        data,
+                        reply,
+                    }) => {
+                        if let Err(err) = self.send(&data) {
+                            let _ = reply.send(Err(err));
+                            self.pending.fail("SSH channel write failed");
+                            return;
+                        }
+                        self.pending.insert(message_id, reply);
+                    }
+                    Ok(Command::Disconnect { reply }) => {
+                        self.ssh_client.set_blocking(true);
+                        let _ = reply.send(self.ssh_client.disconnect());
+                        self.pending.fail("NETCONF session closed");
+                        return;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.close();
+                        return;
+                    }
+                }
+            }
+            if let Err(err) = self.receive().and_then(|_| self.wait()) {
+                self.pending.fail(&err.to_string());
+                return;
+            }
+        }
+    }
+
+    /// Blocks until the socket or the command channel is ready.
+    fn wait(&mut self) -> Result<(), NetconfClientError> {
+        match self.poll.poll(&mut self.events, Some(IDLE_TIMEOUT)) {
+            Err(err) if err.kind() != io::ErrorKind::Interrupted => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn trace(&self, direction: Direction, message: &str) {
+        if let Some(tracer) = self.tracer.read().unwrap().as_ref() {
+            tracer.trace(direction, Some(self.session.session_id), message);
+        }
+    }
+
+    fn send(&mut self, data: &str) -> Result<(), NetconfClientError> {
+        self.trace(Direction::Outbound, data);
+        let data = framing::encode(data, self.session.framing);
+        let mut buf = data.as_bytes();
+        while !buf.is_empty() {
+            match self.ssh_client.write(buf) {
+                Ok(bytes_written) => buf = &buf[bytes_written..],
+                // The server may be blocked on writing to us, so read before
+                // waiting for the window to open again.
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    self.receive()?;
+                    self.wait()?;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<(), NetconfClientError> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match self.ssh_client.read(&mut buffer[..]) {
+                Ok(0) => {
+                    return Err(SSHClientError {
+                        err: "Buffer is empty, SSH channel read terminated".to_string(),
+                    })
+                }
+                Ok(bytes_read) => self.decoder.push(&buffer[..bytes_read]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        while let Some(message) = self.decoder.next_message() {
+            self.trace(Direction::Inbound, &message);
+            self.dispatch(message);
+        }
+        Ok(())
+    }
+
+    fn dispatch(&mut self, message: String) {
+        if messages::is_notification(&message) {
+            let notification = Notification::parse(&message);
+            if notification.is_notification_complete() {
+                self.subscription_active.store(false, Ordering::SeqCst);
+            }
+            let _ = self.notifications.send(notification);
+        } else {
+            self.pending.reply(message);
+        }
+    }
+
+    /// Best effort, nobody is left to report a failure to.
+    fn close(&mut self) {
+        self.ssh_client.set_blocking(true);
+        let message_id = self.id.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = self
+            .ssh_client
+            .write_all(
+                framing::encode(&messages::close_session(message_id), self.session.framing)
+                    .as_bytes(),
+            )
+            .map_err(NetconfClientError::from)
+            .and_then(|_| loop {
+                let message = read_message(&mut self.ssh_client, &mut self.decoder)?;
+                if messages::reply_message_id(&message) == Some(message_id) {
+                    break Ok(());
+                }
+            });
+    }
+}
+
+/// Callers waiting for a reply, by message-id.
+#[derive(Default)]
+struct PendingReplies(HashMap<u32, ReplySender>);
+
+impl PendingReplies {
+    fn insert(&mut self, message_id: u32, reply: ReplySender) {
+        self.0.insert(message_id, reply);
+    }
+
+    /// A reply without a message-id, e.g. to a malformed message, or with an
+    /// unknown one cannot be told apart, so every pending request fails.
+    fn reply(&mut self, message: String) {
+        match messages::reply_message_id(&message).and_then(|message_id| self.0.remove(&message_id))
+        {
+            Some(reply) => {
+                let _ = reply.send(Ok(message));
+            }
+            None => {
+                for (_, reply) in self.0.drain() {
+                    let _ = reply.send(Err(messages::unmatched_reply_error(&message)));
+                }
+            }
+        }
+    }
+
+    fn fail(&mut self, reason: &str) {
+        for (_, reply) in self.0.drain() {
+            let _ = reply.send(Err(SSHClientError {
+                err: reason.to_string(),
+            }));
+        }
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        self.ssh_client.set_blocking(true);
+    }
+}
+
+fn read_message(
+    ssh_client: &mut SSHClient,
+    decoder: &mut MessageDecoder,
+) -> Result<String, NetconfClientError> {
+    loop {
+        if let Some(message) = decoder.next_message() {
+            return Ok(message);
+        }
+        let mut buffer = [0u8; 4096];
+        let bytes_read = ssh_client.read(&mut buffer[..])?;
+        decoder.push(&buffer[..bytes_read]);
+        if bytes_read == 0 || ssh_client.eof()? {
+            if let Some(message) = decoder.next_message() {
+                return Ok(message);
+            }
+            return Err(SSHClientError {
+                err: "Buffer is empty, SSH channel read terminated".to_string(),
+            });
+        }
+    }
+}
+
+fn reader_terminated() -> NetconfClientError {
+    SSHClientError {
+        err: "NETCONF background reader terminated".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::replies::ErrorTagE;
+
+    #[test]
+    fn reply_without_message_id() {
+        let mut pending = PendingReplies::default();
+        let (first_tx, first_rx) = mpsc::channel();
+        let (second_tx, second_rx) = mpsc::channel();
+        pending.insert(1, first_tx);
+        pending.insert(2, second_tx);
+
+        pending.reply(r#"<rpc-reply xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" message-id="2"><ok/></rpc-reply>"#.to_string());
+        assert!(second_rx.try_recv().unwrap().is_ok());
+        assert!(first_rx.try_recv().is_err());
+
+        pending.reply(
+            r#"<rpc-reply xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"><rpc-error><error-type>rpc</error-type><error-tag>malformed-message</error-tag><error-severity>error</error-severity></rpc-error></rpc-reply>"#
+                .to_string(),
+        );
+        match first_rx.try_recv().unwrap() {
+            Err(NetconfClientError::NetconfError { err }) => {
+                assert_eq!(err[0].error_tag.value, ErrorTagE::MalformedMessage)
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+
+        let (third_tx, third_rx) = mpsc::channel();
+        pending.insert(3, third_tx);
+        pending.reply(r#"<rpc-reply xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" message-id="7"><ok/></rpc-reply>"#.to_string());
+        assert!(matches!(
+            third_rx.try_recv().unwrap(),
+            Err(NetconfClientError::NetconfResponseIdError { .. })
+        ));
+    }
+}
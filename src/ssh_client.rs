@@ -11,7 +11,9 @@ pub struct SSHClient {
     port: u16,
     user: String,
     password: String,
+    session: Option<Session>,
     channel: Option<Channel>,
+    stream: Option<TcpStream>,
 }
 
 impl SSHClient {
@@ -21,7 +23,9 @@ impl SSHClient {
             port,
             user: user.to_owned(),
             password: password.to_owned(),
+            session: None,
             channel: None,
+            stream: None,
         }
     }
 
@@ -32,16 +36,36 @@ impl SSHClient {
         let mut session = Session::new()?;
 
         session.set_timeout(SSH_TIMEOUT);
+        let stream = tcp.try_clone()?;
         session.set_tcp_stream(tcp);
         session.handshake()?;
         session.userauth_password(&self.user, &self.password)?;
 
         let mut channel = session.channel_session()?;
         channel.subsystem("netconf")?;
+        self.session = Some(session);
         self.channel = Some(channel);
+        self.stream = Some(stream);
         Ok(())
     }
 
+    pub fn set_blocking(&self, blocking: bool) {
+        if let Some(session) = self.session.as_ref() {
+            session.set_blocking(blocking);
+        }
+    }
+
+    /// Handle to the TCP socket, which libssh2 keeps in non-blocking mode, for
+    /// waiting on readiness.
+    pub(crate) fn try_clone_stream(&self) -> io::Result<TcpStream> {
+        self.stream
+            .as_ref()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotConnected, "SSH channel not connected")
+            })?
+            .try_clone()
+    }
+
     pub fn eof(&self) -> io::Result<bool> {
         Ok(self.channel.as_ref().unwrap().eof())
     }
@@ -53,6 +77,8 @@ impl SSHClient {
         channel.close()?;
         channel.wait_close()?;
         self.channel = None;
+        self.session = None;
+        self.stream = None;
         Ok(())
     }
 }
@@ -61,7 +87,7 @@ impl Drop for SSHClient {
     fn drop(&mut self) {
         if self.channel.is_some() {
             if let Result::Err(err) = self.disconnect() {
-                println!("SSH disconnect error: {}", err.to_string());
+                println!("SSH disconnect error: {}", err);
             }
         }
     }
@@ -0,0 +1,281 @@
+use crate::errors::NetconfClientError;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::BTreeMap;
+
+const WITH_DEFAULTS_ATTRIBUTE_XMLNS: &str = "urn:ietf:params:xml:ns:netconf:default:1.0";
+
+/// Minimal XML element tree for anydata content, e.g. YANG-Push updates.
+/// Whitespace-only text and comments are dropped.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Element {
+    pub name: String,
+    pub prefix: Option<String>,
+    /// Namespace resolved from the declarations in scope.
+    pub namespace: Option<String>,
+    /// `xmlns` declarations of this element, the default namespace has an
+    /// empty prefix.
+    pub declarations: BTreeMap<String, String>,
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Attribute {
+    pub name: String,
+    pub prefix: Option<String>,
+    /// Namespace of the prefix, unprefixed attributes have no namespace.
+    pub namespace: Option<String>,
+    pub value: String,
+}
+
+impl Element {
+    pub fn parse(xml: &str) -> Result<Element, NetconfClientError> {
+        let mut reader = Reader::from_str(xml);
+        let mut buf = Vec::new();
+        let mut stack: Vec<Element> = Vec::new();
+        let mut scopes = vec![BTreeMap::new()];
+        loop {
+            match reader.read_event(&mut buf)? {
+                Event::Start(ref e) => {
+                    let element = Element::start(e, scopes.last().unwrap())?;
+                    let mut scope = scopes.last().unwrap().clone();
+                    scope.extend(element.declarations.clone());
+                    scopes.push(scope);
+                    stack.push(element);
+                }
+                Event::Empty(ref e) => {
+                    let element = Element::start(e, scopes.last().unwrap())?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::End(_) => {
+                    scopes.pop();
+                    let mut element = stack.pop().unwrap();
+                    element.text = element.text.trim().to_string();
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(ref e) | Event::CData(ref e) => {
+                    if let Some(element) = stack.last_mut() {
+                        element
+                            .text
+                            .push_str(&String::from_utf8_lossy(&e.unescaped()?));
+                    }
+                }
+                Event::Eof => {
+                    return Err(NetconfClientError::XMLError(
+                        quick_xml::Error::UnexpectedEof("element".to_string()),
+                    ))
+                }
+                _ => (),
+            }
+            buf.clear();
+        }
+    }
+
+    fn start(
+        e: &BytesStart,
+        scope: &BTreeMap<String, String>,
+    ) -> Result<Element, NetconfClientError> {
+        let qname = String::from_utf8_lossy(e.name()).into_owned();
+        let (prefix, name) = match qname.split_once(':') {
+            Some((prefix, name)) => (Some(prefix.to_string()), name.to_string()),
+            None => (None, qname.clone()),
+        };
+        let mut element = Element {
+            name,
+            prefix,
+            ..Default::default()
+        };
+        for attribute in e.attributes() {
+            let attribute = attribute?;
+            let key = String::from_utf8_lossy(attribute.key).into_owned();
+            let value = String::from_utf8_lossy(&attribute.unescaped_value()?).into_owned();
+            if key == "xmlns" {
+                element.declarations.insert(String::new(), value);
+            } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+                element.declarations.insert(prefix.to_string(), value);
+            } else {
+                let (prefix, name) = match key.split_once(':') {
+                    Some((prefix, name)) => (Some(prefix.to_string()), name.to_string()),
+                    None => (None, key),
+                };
+                element.attributes.push(Attribute {
+                    name,
+                    prefix,
+                    value,
+                    ..Default::default()
+                });
+            }
+        }
+        let resolve = |prefix: &str| {
+            element
+                .declarations
+                .get(prefix)
+                .or_else(|| scope.get(prefix))
+                .cloned()
+        };
+        let namespace = resolve(element.prefix.as_deref().unwrap_or_default());
+        let attribute_namespaces: Vec<_> = element
+            .attributes
+            .iter()
+            .map(|attribute| attribute.prefix.as_deref().and_then(resolve))
+            .collect();
+        element.namespace = namespace;
+        for (attribute, namespace) in element.attributes.iter_mut().zip(attribute_namespaces) {
+            attribute.namespace = namespace;
+        }
+        Ok(element)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut Element> {
+        self.children.iter_mut().find(|child| child.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.as_str())
+    }
+
+    pub fn attribute(&self, namespace: Option<&str>, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name && attribute.namespace.as_deref() == namespace)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Whether the node is tagged with the RFC 6243 `default` attribute, as
+    /// in the `report-all-tagged` with-defaults mode.
+    pub fn is_default(&self) -> bool {
+        matches!(
+            self.attribute(Some(WITH_DEFAULTS_ATTRIBUTE_XMLNS), "default"),
+            Some("true") | Some("1")
+        )
+    }
+
+    /// Copy of the element with the declarations of all namespaces it uses,
+    /// so that it can be serialized outside of its original document.
+    pub fn detached(&self) -> Element {
+        let mut element = self.clone();
+        element.declare_namespaces(&BTreeMap::new());
+        element
+    }
+
+    fn declare_namespaces(&mut self, scope: &BTreeMap<String, String>) {
+        let mut scope = scope.clone();
+        scope.extend(self.declarations.clone());
+        let used = self
+            .attributes
+            .iter()
+            .filter_map(|attribute| Some((attribute.prefix.clone()?, attribute.namespace.clone()?)))
+            .collect::<Vec<_>>();
+        let element = self
+            .namespace
+            .clone()
+            .map(|namespace| (self.prefix.clone().unwrap_or_default(), namespace));
+        for (prefix, namespace) in element.into_iter().chain(used) {
+            if scope.get(&prefix) != Some(&namespace) {
+                self.declarations.insert(prefix.clone(), namespace.clone());
+                scope.insert(prefix, namespace);
+            }
+        }
+        for child in &mut self.children {
+            child.declare_namespaces(&scope);
+        }
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        self.write(&mut xml);
+        xml
+    }
+
+    fn write(&self, xml: &mut String) {
+        let qname = match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        };
+        xml.push('<');
+        xml.push_str(&qname);
+        for (prefix, namespace) in &self.declarations {
+            let key = match prefix.as_str() {
+                "" => "xmlns".to_string(),
+                prefix => format!("xmlns:{}", prefix),
+            };
+            xml.push_str(&format!(r#" {}="{}""#, key, escape_str(namespace)));
+        }
+        for attribute in &self.attributes {
+            let key = match &attribute.prefix {
+                Some(prefix) => format!("{}:{}", prefix, attribute.name),
+                None => attribute.name.clone(),
+            };
+            xml.push_str(&format!(r#" {}="{}""#, key, escape_str(&attribute.value)));
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            xml.push_str("/>");
+            return;
+        }
+        xml.push('>');
+        xml.push_str(&escape_str(&self.text));
+        for child in &self.children {
+            child.write(xml);
+        }
+        xml.push_str(&format!("</{}>", qname));
+    }
+}
+
+fn escape_str(text: &str) -> String {
+    String::from_utf8_lossy(&escape(text.as_bytes())).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_write() {
+        let xml = r#"<notification xmlns="urn:ietf:params:xml:ns:netconf:notification:1.0" xmlns:ex="http://example.com">
+    <ex:interfaces>
+        <ex:interface><ex:name>eth0</ex:name><ex:mtu xmlns:wd="urn:ietf:params:xml:ns:netconf:default:1.0" wd:default="true">1500</ex:mtu></ex:interface>
+        <ex:interface><ex:name>a &amp; b</ex:name></ex:interface>
+    </ex:interfaces>
+</notification>"#;
+        let root = Element::parse(xml).unwrap();
+        assert_eq!(
+            root.namespace.as_deref(),
+            Some("urn:ietf:params:xml:ns:netconf:notification:1.0")
+        );
+        let interfaces = root.child("interfaces").unwrap();
+        assert_eq!(interfaces.namespace.as_deref(), Some("http://example.com"));
+        let names: Vec<_> = interfaces
+            .children_named("interface")
+            .filter_map(|interface| interface.child_text("name"))
+            .collect();
+        assert_eq!(names, vec!["eth0", "a & b"]);
+        let defaults: Vec<_> = interfaces
+            .children_named("interface")
+            .flat_map(|interface| interface.children.iter())
+            .filter(|node| node.is_default())
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(defaults, vec!["mtu"]);
+        assert_eq!(
+            interfaces.detached().to_xml(),
+            r#"<ex:interfaces xmlns:ex="http://example.com"><ex:interface><ex:name>eth0</ex:name><ex:mtu xmlns:wd="urn:ietf:params:xml:ns:netconf:default:1.0" wd:default="true">1500</ex:mtu></ex:interface><ex:interface><ex:name>a &amp; b</ex:name></ex:interface></ex:interfaces>"#
+        );
+    }
+}
@@ -3,9 +3,9 @@ use std::str::FromStr;
 
 use lazy_static::lazy_static;
 use ssh2::Session;
-use tokio;
 
 use config::CONFIG;
+use netconf_client::async_netconf_client::AsyncNetconfClient;
 use netconf_client::netconf_client::NetconfClient;
 
 use crate::common::nc_server::NCServer;
@@ -13,12 +13,12 @@ use crate::common::nc_server::NCServer;
 pub mod config;
 mod nc_server;
 
-pub fn run_test<T>(test: T) -> ()
+pub fn run_test<T>(test: T)
 where
-    T: FnOnce() -> () + std::panic::UnwindSafe,
+    T: FnOnce() + std::panic::UnwindSafe,
 {
     setup();
-    let result = std::panic::catch_unwind(|| test());
+    let result = std::panic::catch_unwind(test);
     teardown();
     assert!(result.is_ok())
 }
@@ -35,10 +35,28 @@ pub fn setup_client() -> NetconfClient {
         &CONFIG.netconf.password,
     );
     client.connect().unwrap();
-    client.send_hello().unwrap();
     client
 }
 
+pub async fn setup_async_client() -> AsyncNetconfClient {
+    let mut client = AsyncNetconfClient::new(
+        &CONFIG.netconf.host,
+        CONFIG.netconf.port,
+        &CONFIG.netconf.user,
+        &CONFIG.netconf.password,
+    );
+    client.connect().await.unwrap();
+    client
+}
+
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
 #[tokio::main]
 async fn setup() {
     SERVER.start().await;
@@ -55,7 +73,7 @@ fn connect_to_netconf(
     user: &str,
     password: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let ip_addr = IpAddr::from_str(&host)?;
+    let ip_addr = IpAddr::from_str(host)?;
     let socket_address: SocketAddr = SocketAddr::from((ip_addr, port));
     let tcp = TcpStream::connect(socket_address)?;
     let mut session = Session::new()?;
@@ -63,7 +81,7 @@ fn connect_to_netconf(
     session.set_timeout(10000);
     session.set_tcp_stream(tcp);
     session.handshake()?;
-    session.userauth_password(&user, &password)?;
+    session.userauth_password(user, password)?;
 
     let mut channel = session.channel_session()?;
     channel.subsystem("netconf")?;
@@ -114,12 +114,14 @@ impl NCServer {
 
     fn wait_for_netconf_ready(&self) {
         loop {
-            if let Ok(_) = connect_to_netconf(
+            if connect_to_netconf(
                 &CONFIG.netconf.host,
                 CONFIG.netconf.port,
                 &CONFIG.netconf.user,
                 &CONFIG.netconf.password,
-            ) {
+            )
+            .is_ok()
+            {
                 break;
             }
         }
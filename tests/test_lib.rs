@@ -1,9 +1,22 @@
 use common::config::CONFIG;
-use common::{run_test, setup_client};
+use common::{block_on, run_test, setup_async_client, setup_client};
+use netconf_client::capabilities::WithDefaultsMode;
+use netconf_client::consumer::{CheckpointStore, DurableConsumer};
 use netconf_client::errors::NetconfClientError::*;
+use netconf_client::mirror::StateMirror;
+use netconf_client::models::netconf_notifications::{ChangedDatastore, NetconfNotification};
+use netconf_client::models::yang_push::{push_updates, YangPushUpdate};
 use netconf_client::models::{replies::*, requests::*};
 use netconf_client::netconf_client::NetconfClient;
+use netconf_client::observer::{Direction, MessageObserver, Redactor, TracedMessage};
+use netconf_client::schema_cache::{SchemaCache, SchemaId};
+use netconf_client::session::{ClientCapabilities, Framing, BASE_1_0, BASE_1_1};
+use netconf_client::shared_netconf_client::SharedNetconfClient;
+use serde::{Deserialize, Serialize};
 use serial_test::serial;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 mod common;
 
@@ -17,8 +30,85 @@ fn connect() {
             &CONFIG.netconf.user,
             &CONFIG.netconf.password,
         );
+        let hello = client.connect().unwrap();
+        let session = client.get_session().unwrap();
+        assert_eq!(session.session_id, hello.session_id);
+        assert_eq!(
+            session.server_capabilities.uris(),
+            &hello.capabilities.capabilities[..]
+        );
+        assert_eq!(session.client_capabilities, vec![BASE_1_0, BASE_1_1]);
+        assert_eq!(session.framing, Framing::Chunked);
+    });
+}
+
+#[test]
+#[serial]
+fn server_capabilities() {
+    run_test(|| {
+        let client = setup_client();
+        let capabilities = client.server_capabilities().unwrap();
+        assert!(capabilities.base_1_1());
+        assert!(capabilities.candidate());
+        assert!(capabilities.startup());
+        assert!(capabilities.xpath());
+        assert!(capabilities.with_defaults().is_some());
+        assert!(capabilities.yang_library().is_some());
+        assert!(capabilities.module("ietf-netconf").is_some());
+    });
+}
+
+#[test]
+#[serial]
+fn connect_base_1_0() {
+    run_test(|| {
+        let mut client = NetconfClient::new(
+            &CONFIG.netconf.host,
+            CONFIG.netconf.port,
+            &CONFIG.netconf.user,
+            &CONFIG.netconf.password,
+        );
+        client.set_client_capabilities(
+            ClientCapabilities::default()
+                .with_base_1_1(false)
+                .with_capability("urn:ietf:params:netconf:capability:startup:1.0"),
+        );
         client.connect().unwrap();
-        client.send_hello().unwrap();
+        assert_eq!(client.get_session().unwrap().framing, Framing::EndOfMessage);
+        client.get(None, None).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn reconnect_base_1_1() {
+    run_test(|| {
+        let mut client = setup_client();
+        let session_id = client.get_session_id().unwrap();
+        setup_client().kill_session(session_id).unwrap();
+
+        let hello = client.connect().unwrap();
+        assert_ne!(hello.session_id, session_id);
+        assert_eq!(client.get_session().unwrap().framing, Framing::Chunked);
+        client.get(None, None).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn async_reconnect_base_1_1() {
+    run_test(|| {
+        block_on(async {
+            let mut client = setup_async_client().await;
+            let session_id = client.get_session_id().unwrap();
+            setup_client().kill_session(session_id).unwrap();
+
+            let hello = client.connect().await.unwrap();
+            assert_ne!(hello.session_id, session_id);
+            assert_eq!(client.get_session().unwrap().framing, Framing::Chunked);
+            client.get(None, None).await.unwrap();
+            client.close_session().await.unwrap();
+        });
     });
 }
 
@@ -96,10 +186,10 @@ fn get() {
             .unwrap();
 
         let rsp = client
-            .get(Some(Filter {
-                filter_type: FilterType::Subtree,
-                data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-            }))
+            .get(
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
+            )
             .unwrap();
         assert_eq!(
             rsp.data,
@@ -127,10 +217,8 @@ fn edit_config_running_database() {
         let rsp = client
             .get_config(
                 DatastoreType::Running,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(
@@ -162,16 +250,15 @@ fn edit_config_copy_config() {
                 CopyConfigSourceType::Datastore {
                     source: DatastoreType::Running,
                 },
+                None,
             )
             .unwrap();
 
         let rsp = client
             .get_config(
                 DatastoreType::Startup,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(
@@ -184,10 +271,8 @@ fn edit_config_copy_config() {
         let rsp = client
             .get_config(
                 DatastoreType::Startup,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(rsp.data, Some("".to_string()));
@@ -203,10 +288,8 @@ fn delete_startup_data() {
         let rsp = client
             .get_config(
                 DatastoreType::Startup,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(
@@ -219,10 +302,8 @@ fn delete_startup_data() {
         let rsp = client
             .get_config(
                 DatastoreType::Startup,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(rsp.data, Some("".to_string()));
@@ -250,10 +331,8 @@ fn edit_config_candidate_then_commit() {
         let rsp = client
             .get_config(
                 DatastoreType::Running,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(
@@ -284,12 +363,948 @@ fn discard_changes() {
         let rsp = client
             .get_config(
                 DatastoreType::Running,
-                Some(Filter {
-                    filter_type: FilterType::Subtree,
-                    data: r#"<users xmlns="ns:yang:test"></users>"#.to_string(),
-                }),
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                None,
             )
             .unwrap();
         assert_eq!(rsp.data, Some("".to_string()));
     });
 }
+
+#[test]
+#[serial]
+fn async_edit_config_candidate_then_commit() {
+    run_test(|| {
+        block_on(async {
+            let mut client = setup_async_client().await;
+
+            client.lock(DatastoreType::Candidate).await.unwrap();
+            client
+                .edit_config(
+                    DatastoreType::Candidate,
+                    r#"<users xmlns="ns:yang:test"><name>Oliver</name></users>"#.to_string(),
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            client.commit().await.unwrap();
+            client.unlock(DatastoreType::Candidate).await.unwrap();
+
+            let rsp = client
+                .get_config(
+                    DatastoreType::Running,
+                    Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                    None,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                rsp.data,
+                Some("<users xmlns=\"ns:yang:test\"><name>Oliver</name></users>".to_string())
+            );
+
+            client.close_session().await.unwrap();
+        });
+    });
+}
+
+#[test]
+#[serial]
+fn async_cancelled_request() {
+    run_test(|| {
+        block_on(async {
+            let mut client = setup_async_client().await;
+
+            {
+                let cancelled = client.get(None, None);
+                futures_util::pin_mut!(cancelled);
+                let _ = futures_util::poll!(cancelled);
+            }
+
+            client.lock(DatastoreType::Running).await.unwrap();
+            client.unlock(DatastoreType::Running).await.unwrap();
+            client.close_session().await.unwrap();
+        });
+    });
+}
+
+#[test]
+#[serial]
+fn shared_client_concurrent_requests() {
+    run_test(|| {
+        let client = SharedNetconfClient::connect(
+            &CONFIG.netconf.host,
+            CONFIG.netconf.port,
+            &CONFIG.netconf.user,
+            &CONFIG.netconf.password,
+        )
+        .unwrap();
+
+        client
+            .edit_config(
+                DatastoreType::Running,
+                r#"<users xmlns="ns:yang:test"><name>Jack</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                std::thread::spawn(move || {
+                    client
+                        .get_config(
+                            DatastoreType::Running,
+                            Some(Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#)),
+                            None,
+                        )
+                        .unwrap()
+                })
+            })
+            .collect();
+        for worker in workers {
+            assert_eq!(
+                worker.join().unwrap().data,
+                Some("<users xmlns=\"ns:yang:test\"><name>Jack</name></users>".to_string())
+            );
+        }
+
+        client.close_session().unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn raw_rpc() {
+    run_test(|| {
+        let mut client = setup_client();
+
+        let rsp = client
+            .raw_rpc(
+                r#"<get-config><source><startup/></source><filter type="subtree"><users xmlns="ns:yang:test"/></filter></get-config>"#,
+            )
+            .unwrap();
+        assert_eq!(
+            NetconfClient::get_data(&rsp.reply),
+            Some(r#"<users xmlns="ns:yang:test"><name>Harry</name></users>"#)
+        );
+
+        let rsp = client
+            .raw_rpc(r#"<unknown-rpc xmlns="ns:yang:test"/>"#)
+            .unwrap_err();
+        match rsp {
+            NetconfError { err: errors } => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].error_severity.value, ErrorSeverityE::Error);
+            }
+            _ => panic!("Wrong error type {:#?}", rsp),
+        }
+    });
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    messages: Mutex<Vec<TracedMessage>>,
+}
+
+impl MessageObserver for RecordingObserver {
+    fn on_message(&self, message: &TracedMessage) {
+        self.messages.lock().unwrap().push(message.clone());
+    }
+}
+
+#[test]
+#[serial]
+fn observer_redacts_secrets() {
+    run_test(|| {
+        let mut client = setup_client();
+        let observer = Arc::new(RecordingObserver::default());
+        client.set_observer(
+            observer.clone(),
+            Redactor::default().with_leaves(vec!["name"]),
+        );
+
+        client
+            .edit_config(
+                DatastoreType::Candidate,
+                r#"<users xmlns="ns:yang:test"><name>Alice</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        client.discard_changes().unwrap();
+
+        let messages = observer.messages.lock().unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].direction, Direction::Outbound);
+        assert_eq!(messages[1].direction, Direction::Inbound);
+        assert_eq!(messages[0].message_id, messages[1].message_id);
+        assert_eq!(messages[0].session_id, client.get_session_id());
+        assert!(messages[0].message.contains("<name>***</name>"));
+        assert!(!messages[0].message.contains("Alice"));
+    });
+}
+
+#[test]
+#[serial]
+fn yang_library() {
+    run_test(|| {
+        let mut client = setup_client();
+
+        let library = client.get_yang_library().unwrap();
+        let module = library.module("ietf-netconf").unwrap();
+        assert_eq!(module.namespace, "urn:ietf:params:xml:ns:netconf:base:1.0");
+        assert!(!library.datastore_module_sets("ds:running").is_empty());
+        assert_eq!(client.get_yang_library().unwrap(), library);
+
+        let state = client.get_modules_state().unwrap();
+        assert!(state.module("ietf-netconf").is_some());
+        client.invalidate_yang_library_cache();
+        assert_eq!(client.get_modules_state().unwrap(), state);
+    });
+}
+
+#[test]
+#[serial]
+fn validate() {
+    run_test(|| {
+        let mut client = setup_client();
+
+        client
+            .edit_config(
+                DatastoreType::Candidate,
+                r#"<users xmlns="ns:yang:test"><name>Alice</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let rsp = client
+            .validate(ValidateSourceType::Datastore {
+                source: DatastoreType::Candidate,
+            })
+            .unwrap();
+        assert!(rsp.is_ok());
+        client.discard_changes().unwrap();
+
+        let rsp = client
+            .validate(ValidateSourceType::Config {
+                config: Data {
+                    xmlns_xc: None,
+                    data: r#"<users xmlns="ns:yang:test"><name>Bob</name></users>"#.to_string(),
+                },
+            })
+            .unwrap();
+        assert!(rsp.is_ok());
+    });
+}
+
+#[test]
+#[serial]
+fn confirmed_commit() {
+    run_test(|| {
+        let mut client = setup_client();
+
+        client
+            .edit_config(
+                DatastoreType::Candidate,
+                r#"<users xmlns="ns:yang:test"><name>Alice</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        client
+            .confirmed_commit(Some(60), Some("change-1".to_string()))
+            .unwrap();
+
+        let mut client2 = setup_client();
+        client2.cancel_commit(Some("change-1".to_string())).unwrap();
+
+        let rsp = client
+            .get_config(
+                DatastoreType::Running,
+                Some(Filter::subtree(r#"<users xmlns="ns:yang:test"/>"#)),
+                None,
+            )
+            .unwrap();
+        assert!(!rsp.data.unwrap().contains("Alice"));
+
+        client.confirmed_commit(Some(60), None).unwrap();
+        client.confirm_commit(None).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn partial_lock() {
+    run_test(|| {
+        let mut client = setup_client();
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("t".to_string(), "ns:yang:test".to_string());
+
+        if !client.server_capabilities().unwrap().partial_lock() {
+            match client.partial_lock(vec!["/t:users".to_string()], &namespaces) {
+                Err(CapabilityNotSupported { operation, .. }) => {
+                    assert_eq!(operation, "partial-lock")
+                }
+                rsp => panic!("Unexpected response {:#?}", rsp),
+            }
+            return;
+        }
+
+        let rsp = client
+            .partial_lock(vec!["/t:users".to_string()], &namespaces)
+            .unwrap();
+        let lock_id = rsp.lock_id.unwrap();
+        assert!(!rsp.locked_nodes.is_empty());
+
+        let mut client2 = setup_client();
+        let err = client2
+            .partial_lock(vec!["/t:users".to_string()], &namespaces)
+            .unwrap_err();
+        match err {
+            NetconfError { err: errors } => {
+                let lock_denied = errors[0].lock_denied().unwrap();
+                assert_eq!(lock_denied.lock_ids, vec![lock_id]);
+            }
+            _ => panic!("Wrong error type {:#?}", err),
+        }
+
+        client.partial_unlock(lock_id).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn get_schema() {
+    run_test(|| {
+        let mut client = setup_client();
+
+        let rsp = client
+            .get_schema("ietf-netconf", None, Some(SchemaFormat::Yang))
+            .unwrap();
+        assert!(rsp.schema.starts_with("module ietf-netconf"));
+
+        let rsp = client
+            .get_schema("ietf-netconf", None, Some(SchemaFormat::Yin))
+            .unwrap();
+        assert!(rsp.schema.starts_with("<module"));
+
+        let directory = std::env::temp_dir().join("netconf-client-schemas");
+        let _ = std::fs::remove_dir_all(&directory);
+        let cache = SchemaCache::new(&directory);
+        let downloaded = client.download_schemas(&cache, SchemaFormat::Yang).unwrap();
+        assert!(!downloaded.is_empty());
+        let library = client.get_yang_library().unwrap();
+        let module = library.module("ietf-netconf").unwrap();
+        assert!(cache
+            .contains(
+                &SchemaId {
+                    identifier: module.name.clone(),
+                    revision: module.revision.clone(),
+                },
+                SchemaFormat::Yang
+            )
+            .unwrap());
+        assert!(client
+            .download_schemas(&cache, SchemaFormat::Yang)
+            .unwrap()
+            .is_empty());
+        std::fs::remove_dir_all(&directory).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn netconf_state() {
+    run_test(|| {
+        let mut client = setup_client();
+        let session_id = client.get_session_id().unwrap();
+
+        client.lock(DatastoreType::Running).unwrap();
+        let datastores = client.get_datastores().unwrap();
+        let running = datastores.iter().find(|d| d.name == "running").unwrap();
+        let global_lock = running.locks.as_ref().unwrap().global_lock.as_ref();
+        assert_eq!(global_lock.unwrap().locked_by_session, session_id);
+        client.unlock(DatastoreType::Running).unwrap();
+
+        let sessions = client.get_sessions().unwrap();
+        let session = sessions
+            .iter()
+            .find(|s| s.session_id == session_id)
+            .unwrap();
+        assert_eq!(session.username, CONFIG.netconf.user);
+        assert!(session.in_rpcs > 0);
+
+        assert!(client.get_statistics().unwrap().in_sessions > 0);
+        assert!(client
+            .get_schema_list()
+            .unwrap()
+            .iter()
+            .any(|schema| schema.identifier == "ietf-netconf"));
+        let state = client.get_netconf_state().unwrap();
+        assert!(state.sessions.is_some() && state.statistics.is_some());
+    });
+}
+
+#[test]
+#[serial]
+fn create_subscription() {
+    run_test(|| {
+        let mut client = setup_client();
+        assert!(client.server_capabilities().unwrap().interleave());
+        client
+            .create_subscription(
+                Some("NETCONF"),
+                Some(NotificationFilter::Subtree(
+                    r#"<netconf-config-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"/>"#.to_string(),
+                )),
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(client.subscription_active());
+
+        client
+            .edit_config(
+                DatastoreType::Candidate,
+                r#"<users xmlns="ns:yang:test"><name>Bob</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        client.commit().unwrap();
+
+        let notification = client.notifications().next().unwrap().unwrap();
+        assert_eq!(notification.event_name, "netconf-config-change");
+        assert!(!notification.event_time.is_empty());
+        assert!(notification.event.contains("ns:yang:test"));
+    });
+}
+
+#[test]
+#[serial]
+fn notifications_wait_past_ssh_timeout() {
+    run_test(|| {
+        let mut client = setup_client();
+        client
+            .create_subscription(Some("NETCONF"), None, None, None)
+            .unwrap();
+        let editor = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_secs(12));
+            setup_client()
+                .edit_config(
+                    DatastoreType::Running,
+                    r#"<users xmlns="ns:yang:test"><name>Judy</name></users>"#.to_string(),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        });
+
+        let notification = client
+            .notifications()
+            .find(|notification| match notification {
+                Ok(notification) => notification.event_name == "netconf-config-change",
+                Err(_) => true,
+            })
+            .unwrap()
+            .unwrap();
+        assert!(notification.event.contains("ns:yang:test"));
+        editor.join().unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn create_subscription_replay() {
+    run_test(|| {
+        let mut client = setup_client();
+        client
+            .create_subscription(
+                Some("NETCONF"),
+                None,
+                Some("2021-01-01T00:00:00Z"),
+                Some("2021-01-01T00:00:01Z"),
+            )
+            .unwrap();
+        let notifications = client
+            .notifications()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(notifications.iter().any(|n| n.is_replay_complete()));
+        assert!(notifications.last().unwrap().is_notification_complete());
+        assert!(!client.subscription_active());
+    });
+}
+
+#[test]
+#[serial]
+fn establish_subscription() {
+    run_test(|| {
+        let mut client = setup_client();
+        let rsp = client
+            .establish_subscription(StreamSubscription {
+                stream: "NETCONF".to_string(),
+                filter: Some(NotificationFilter::Subtree(
+                    r#"<netconf-config-change xmlns="urn:ietf:params:xml:ns:yang:ietf-netconf-notifications"/>"#.to_string(),
+                )),
+                encoding: Some(SubscriptionEncoding::EncodeXml),
+                ..Default::default()
+            })
+            .unwrap();
+        let id = rsp.id.unwrap();
+        assert!(client.dynamic_subscriptions().contains(&id));
+
+        client
+            .edit_config(
+                DatastoreType::Running,
+                r#"<users xmlns="ns:yang:test"><name>Carol</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let notification = client.next_notification().unwrap().unwrap();
+        assert_eq!(notification.event_name, "netconf-config-change");
+        match NetconfNotification::from_notification(&notification).unwrap() {
+            Some(NetconfNotification::ConfigChange(change)) => {
+                assert_eq!(change.datastore, ChangedDatastore::Running);
+                assert!(change.changed_by.session_id.is_some());
+            }
+            notification => panic!("Unexpected notification {:?}", notification),
+        }
+
+        client
+            .modify_subscription(id, None, Some("2099-01-01T00:00:00Z"))
+            .unwrap();
+        client.delete_subscription(id).unwrap();
+        assert!(client.dynamic_subscriptions().is_empty());
+        match client.delete_subscription(id).unwrap_err() {
+            NetconfError { .. } => (),
+            err => panic!("Wrong error type {:#?}", err),
+        }
+    });
+}
+
+#[test]
+#[serial]
+fn establish_datastore_subscription() {
+    run_test(|| {
+        let mut client = setup_client();
+        let rsp = client
+            .establish_datastore_subscription(DatastoreSubscription {
+                datastore: "running".to_string(),
+                filter: Some(NotificationFilter::Subtree(
+                    r#"<users xmlns="ns:yang:test"/>"#.to_string(),
+                )),
+                trigger: UpdateTrigger::Periodic {
+                    period: 100,
+                    anchor_time: None,
+                },
+                stop_time: None,
+                encoding: Some(SubscriptionEncoding::EncodeXml),
+                dscp: None,
+            })
+            .unwrap();
+        let id = rsp.id.unwrap();
+        assert!(client.dynamic_subscriptions().contains(&id));
+
+        let update = push_updates(client.notifications())
+            .next()
+            .unwrap()
+            .unwrap();
+        match update {
+            YangPushUpdate::Update(update) => assert_eq!(update.id, id),
+            update => panic!("Unexpected update {:?}", update),
+        }
+        client.delete_subscription(id).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn state_mirror() {
+    run_test(|| {
+        let mut client = setup_client();
+        let mut mirror = StateMirror::new(r#"<users xmlns="ns:yang:test"/>"#);
+        mirror.resync(&mut client).unwrap();
+        assert!(!mirror.is_stale());
+        assert!(mirror.subscription().is_some());
+
+        client
+            .edit_config(
+                DatastoreType::Running,
+                r#"<users xmlns="ns:yang:test"><name>Dave</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let contains_dave = |mirror: &StateMirror| {
+            mirror
+                .data()
+                .iter()
+                .any(|users| users.child_text("name") == Some("Dave"))
+        };
+        for _ in 0..10 {
+            if contains_dave(&mirror) {
+                break;
+            }
+            mirror.update(&mut client).unwrap();
+        }
+        assert!(contains_dave(&mirror));
+    });
+}
+
+#[test]
+#[serial]
+fn state_mirror_reconnect() {
+    run_test(|| {
+        let mut client = setup_client();
+        let mut mirror = StateMirror::new(r#"<users xmlns="ns:yang:test"/>"#);
+        mirror.resync(&mut client).unwrap();
+        let session_id = client.get_session_id().unwrap();
+
+        let mut editor = setup_client();
+        editor.kill_session(session_id).unwrap();
+        editor
+            .edit_config(
+                DatastoreType::Running,
+                r#"<users xmlns="ns:yang:test"><name>Grace</name></users>"#.to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        mirror.update(&mut client).unwrap();
+        assert_ne!(client.get_session_id(), Some(session_id));
+        assert!(!mirror.is_stale());
+        assert!(mirror.subscription().is_some());
+        assert!(mirror
+            .data()
+            .iter()
+            .any(|users| users.child_text("name") == Some("Grace")));
+    });
+}
+
+#[test]
+#[serial]
+fn durable_consumer() {
+    run_test(|| {
+        let path = std::env::temp_dir().join("netconf-client-test-checkpoints");
+        let _ = std::fs::remove_file(&path);
+        let edit_user = |client: &mut NetconfClient, name: &str| {
+            client
+                .edit_config(
+                    DatastoreType::Running,
+                    format!(
+                        r#"<users xmlns="ns:yang:test"><name>{}</name></users>"#,
+                        name
+                    ),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        };
+        let next_config_change = |consumer: &mut DurableConsumer, client: &mut NetconfClient| loop {
+            let notification = consumer.next(client).unwrap().unwrap();
+            if notification.event_name == "netconf-config-change" {
+                return notification;
+            }
+            consumer.acknowledge(&notification).unwrap();
+        };
+
+        let mut client = setup_client();
+        let mut consumer =
+            DurableConsumer::new("test", "NETCONF", CheckpointStore::new(&path)).unwrap();
+        assert!(consumer.checkpoint().is_none());
+        // Subscribes and waits until the stream is idle.
+        while let Some(notification) = consumer.next(&mut client).unwrap() {
+            consumer.acknowledge(&notification).unwrap();
+        }
+        let mut editor = setup_client();
+        edit_user(&mut editor, "Erin");
+        let first = next_config_change(&mut consumer, &mut client);
+        consumer.acknowledge(&first).unwrap();
+        drop(client);
+
+        let mut client = setup_client();
+        let mut consumer =
+            DurableConsumer::new("test", "NETCONF", CheckpointStore::new(&path)).unwrap();
+        assert_eq!(
+            consumer
+                .checkpoint()
+                .map(|checkpoint| checkpoint.event_time.as_str()),
+            Some(first.event_time.as_str())
+        );
+        edit_user(&mut editor, "Frank");
+        let second = next_config_change(&mut consumer, &mut client);
+        assert_ne!(second, first);
+        std::fs::remove_file(&path).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn durable_consumer_reconnect() {
+    run_test(|| {
+        let path = std::env::temp_dir().join("netconf-client-test-reconnect-checkpoints");
+        let _ = std::fs::remove_file(&path);
+        let edit_user = |client: &mut NetconfClient, name: &str| {
+            client
+                .edit_config(
+                    DatastoreType::Running,
+                    format!(
+                        r#"<users xmlns="ns:yang:test"><name>{}</name></users>"#,
+                        name
+                    ),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        };
+        let next_config_change = |consumer: &mut DurableConsumer, client: &mut NetconfClient| loop {
+            let notification = consumer.next(client).unwrap().unwrap();
+            consumer.acknowledge(&notification).unwrap();
+            if notification.event_name == "netconf-config-change" {
+                return notification;
+            }
+        };
+
+        let mut client = setup_client();
+        let mut consumer = DurableConsumer::new("test", "NETCONF", CheckpointStore::new(&path))
+            .unwrap()
+            .with_retry_interval(Duration::from_millis(100));
+        let mut editor = setup_client();
+        while let Some(notification) = consumer.next(&mut client).unwrap() {
+            consumer.acknowledge(&notification).unwrap();
+        }
+        edit_user(&mut editor, "Heidi");
+        let first = next_config_change(&mut consumer, &mut client);
+        let session_id = client.get_session_id().unwrap();
+
+        editor.kill_session(session_id).unwrap();
+        edit_user(&mut editor, "Ivan");
+        let second = next_config_change(&mut consumer, &mut client);
+        assert_ne!(client.get_session_id(), Some(session_id));
+        assert_ne!(second, first);
+        std::fs::remove_file(&path).unwrap();
+    });
+}
+
+#[test]
+#[serial]
+fn nmda_get_and_edit_data() {
+    run_test(|| {
+        let mut client = setup_client();
+        let rsp = client
+            .edit_data(
+                Datastore::running(),
+                r#"<users xmlns="ns:yang:test"><name>Grace</name></users>"#.to_string(),
+                Some(DefaultOperationType::Merge),
+            )
+            .unwrap();
+        assert!(rsp.ok.is_some());
+
+        let filter = Filter::subtree(r#"<users xmlns="ns:yang:test"/>"#);
+        let rsp = client
+            .nmda_get_data(
+                Datastore::running(),
+                GetDataOptions {
+                    filter: Some(filter.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(rsp.data.unwrap().contains("Grace"));
+
+        let rsp = client
+            .nmda_get_data(
+                Datastore::operational(),
+                GetDataOptions {
+                    filter: Some(filter),
+                    max_depth: Some(2),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(rsp.data.unwrap().contains("Grace"));
+    });
+}
+
+#[test]
+#[serial]
+fn get_config_with_defaults() {
+    run_test(|| {
+        let mut client = setup_client();
+        let filter = Filter::subtree(r#"<users xmlns="ns:yang:test"></users>"#);
+        let rsp = client
+            .get_config(
+                DatastoreType::Running,
+                Some(filter.clone()),
+                Some(WithDefaultsMode::ReportAllTagged),
+            )
+            .unwrap();
+        let nodes = rsp.data_nodes.unwrap();
+        assert_eq!(nodes[0].name, "users");
+        assert!(!nodes[0].is_default());
+
+        let rsp = client
+            .get(Some(filter), Some(WithDefaultsMode::Trim))
+            .unwrap();
+        assert!(rsp.data.unwrap().contains("users"));
+        assert!(rsp.data_nodes.is_none());
+    });
+}
+
+#[test]
+#[serial]
+fn get_config_xpath_filter() {
+    run_test(|| {
+        let mut client = setup_client();
+        assert!(client.server_capabilities().unwrap().xpath());
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("t".to_string(), "ns:yang:test".to_string());
+        let rsp = client
+            .get_config(
+                DatastoreType::Running,
+                Some(Filter::xpath("/t:users/t:name", namespaces)),
+                None,
+            )
+            .unwrap();
+        assert!(rsp.data.unwrap().contains("<name>"));
+
+        let rsp = client
+            .get(Some(Filter::xpath("/test:users", BTreeMap::new())), None)
+            .unwrap();
+        assert!(rsp.data.unwrap().contains("users"));
+    });
+}
+
+#[test]
+#[serial]
+fn copy_config_url() {
+    run_test(|| {
+        let mut client = setup_client();
+        let url = "file:///tmp/netconf-client-backup.xml";
+        let supported = client
+            .server_capabilities()
+            .unwrap()
+            .url_schemes()
+            .is_some_and(|schemes| schemes.iter().any(|scheme| scheme == "file"));
+        let rsp = client.copy_config(
+            ConfigTargetType::Url { url: Url::new(url) },
+            CopyConfigSourceType::Datastore {
+                source: DatastoreType::Running,
+            },
+            None,
+        );
+        if !supported {
+            match rsp {
+                Err(CapabilityNotSupported { capability, .. }) => assert_eq!(
+                    capability,
+                    "urn:ietf:params:netconf:capability:url:1.0?scheme=file"
+                ),
+                rsp => panic!("Unexpected result {:?}", rsp),
+            }
+            return;
+        }
+        assert!(rsp.unwrap().ok.is_some());
+
+        let rsp = client
+            .edit_config_url(
+                DatastoreType::Running,
+                url,
+                Some(DefaultOperationType::Merge),
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(rsp.ok.is_some());
+
+        let rsp = client
+            .delete_config(ConfigTargetType::Url { url: Url::new(url) })
+            .unwrap();
+        assert!(rsp.ok.is_some());
+    });
+}
+
+#[test]
+#[serial]
+fn typed_rpc() {
+    #[derive(Serialize)]
+    struct Identifier {
+        #[serde(rename = "$value")]
+        value: String,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename = "get-schema")]
+    struct GetSchema {
+        identifier: Identifier,
+    }
+
+    #[derive(Deserialize)]
+    struct GetSchemaOutput {
+        data: String,
+    }
+
+    run_test(|| {
+        let mut client = setup_client();
+        let rsp: GetSchemaOutput = client
+            .rpc(
+                "urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring",
+                &GetSchema {
+                    identifier: Identifier {
+                        value: "test".to_string(),
+                    },
+                },
+            )
+            .unwrap();
+        assert!(rsp.data.contains("module test"));
+
+        let rsp: Result<GetSchemaOutput, _> = client.rpc(
+            "urn:ietf:params:xml:ns:yang:ietf-netconf-monitoring",
+            &GetSchema {
+                identifier: Identifier {
+                    value: "no-such-module".to_string(),
+                },
+            },
+        );
+        assert!(matches!(rsp, Err(NetconfError { .. })));
+    });
+}
+
+#[test]
+#[serial]
+fn yang_action() {
+    #[derive(Serialize)]
+    #[serde(rename = "reset")]
+    struct Reset {}
+
+    #[derive(Deserialize)]
+    struct ResetOutput {}
+
+    run_test(|| {
+        let mut client = setup_client();
+        let path = ActionPath::new("ns:yang:test", "users").node("name");
+        let rsp: Result<ResetOutput, _> = client.action(&path, &Reset {});
+        match rsp {
+            Err(NetconfError { err }) => assert!(!err.is_empty()),
+            _ => panic!("action not defined in the test model"),
+        }
+        assert!(client.get(None, None).is_ok());
+    });
+}